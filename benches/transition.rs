@@ -0,0 +1,56 @@
+//! Measures how [GameOfLife::transition_parallel] scales with the number of threads, compared
+//! to the serial [GameOfLife::transition].
+//!
+//! Run with `cargo bench --bench transition`.
+
+use std::time::{Duration, Instant};
+
+use gol::game_of_life::GameOfLife;
+
+const SIZE: usize = 2000;
+const RUNS: u32 = 5;
+
+fn soup(width: usize, height: usize) -> GameOfLife {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state.is_multiple_of(3)
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<bool>>>()
+        .into()
+}
+
+fn time(mut f: impl FnMut() -> GameOfLife) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / RUNS
+}
+
+fn main() {
+    let board = soup(SIZE, SIZE);
+    let serial = time(|| board.transition());
+    println!("{SIZE}x{SIZE} board, mean of {RUNS} generations");
+    println!("serial:      {:>8.1?}", serial);
+
+    let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let mut threads = 1;
+    while threads <= cores.max(4) {
+        let parallel = time(|| board.transition_parallel(threads));
+        println!(
+            "{threads:>2} threads:  {:>8.1?}  ({:.2}x)",
+            parallel,
+            serial.as_secs_f64() / parallel.as_secs_f64()
+        );
+        threads *= 2;
+    }
+    assert_eq!(board.transition_parallel(cores), board.transition());
+}
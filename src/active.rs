@@ -0,0 +1,216 @@
+use crate::{
+    error::Error, game_of_life::GameOfLife, neighborhood::Neighborhood, read::Read, write::Write,
+};
+
+const DEFAULT_TILE_SIZE: usize = 32;
+
+/// How much work a [ActiveLife] did, or avoided doing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TileStats {
+    /// Tiles whose cells were recomputed.
+    pub computed: u64,
+    /// Tiles that were skipped, because nothing in or around them changed.
+    pub skipped: u64,
+}
+
+/// A [GameOfLife] that skips the parts of the board that have settled down.
+///
+/// The board is split into square tiles. A tile is only recomputed when a cell in it, or in one
+/// of the tiles around it, changed in the previous generation. Mostly empty or stable boards
+/// therefore only pay for the regions where something is happening.
+#[derive(Debug, Clone)]
+pub struct ActiveLife {
+    board: GameOfLife,
+    tile_size: usize,
+    tiles_x: usize,
+    tiles_y: usize,
+    /// The tiles each tile depends on, including itself.
+    neighbors: Vec<Vec<usize>>,
+    changed: Vec<bool>,
+    next_changed: Vec<bool>,
+    last: TileStats,
+    total: TileStats,
+}
+
+impl ActiveLife {
+    /// Wraps a board, using 32x32 tiles.
+    pub fn new(board: GameOfLife) -> Self {
+        let mut active = ActiveLife {
+            board,
+            tile_size: DEFAULT_TILE_SIZE,
+            tiles_x: 0,
+            tiles_y: 0,
+            neighbors: Vec::new(),
+            changed: Vec::new(),
+            next_changed: Vec::new(),
+            last: TileStats::default(),
+            total: TileStats::default(),
+        };
+        active.build_tiles();
+        active
+    }
+
+    /// Sets the width and height of the tiles. Smaller tiles skip more precisely but cost more to
+    /// track.
+    pub fn with_tile_size(self, tile_size: usize) -> Self {
+        let mut active = Self {
+            tile_size: tile_size.max(1),
+            ..self
+        };
+        active.build_tiles();
+        active
+    }
+
+    /// The width and height of the tiles.
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// The current generation of the board.
+    pub fn board(&self) -> &GameOfLife {
+        &self.board
+    }
+
+    /// Unwraps the board.
+    pub fn into_inner(self) -> GameOfLife {
+        self.board
+    }
+
+    /// The work done by the most recent step.
+    pub fn last_stats(&self) -> TileStats {
+        self.last
+    }
+
+    /// The work done by every step so far.
+    pub fn total_stats(&self) -> TileStats {
+        self.total
+    }
+
+    /// Advance the board a single generation in place, only recomputing tiles near changes.
+    pub fn step(&mut self) {
+        let (width, height) = (self.board.width, self.board.height);
+        let mut next = std::mem::take(&mut self.board.scratch);
+        let mut stats = TileStats::default();
+        for tile in 0..self.changed.len() {
+            self.next_changed[tile] = false;
+            // A tile with nothing changing around it will come out the same as last time, which
+            // is what the spare buffer already holds for it.
+            if !self.neighbors[tile].iter().any(|t| self.changed[*t]) {
+                stats.skipped += 1;
+                continue;
+            }
+            stats.computed += 1;
+            let (x0, y0) = (
+                tile % self.tiles_x * self.tile_size,
+                tile / self.tiles_x * self.tile_size,
+            );
+            for y in y0..usize::min(y0 + self.tile_size, height) {
+                for x in x0..usize::min(x0 + self.tile_size, width) {
+                    let i = y * width + x;
+                    let alive = self.board.next_cell(x, y);
+                    self.next_changed[tile] |= alive != self.board.cells[i];
+                    next[i] = alive;
+                }
+            }
+        }
+        self.board.scratch = std::mem::replace(&mut self.board.cells, next);
+        std::mem::swap(&mut self.changed, &mut self.next_changed);
+        self.last = stats;
+        self.total.computed += stats.computed;
+        self.total.skipped += stats.skipped;
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    fn tile_of(&self, x: usize, y: usize) -> usize {
+        y / self.tile_size * self.tiles_x + x / self.tile_size
+    }
+
+    /// Works out which tiles each tile reads from, and marks every tile as changed so the first
+    /// step computes the whole board.
+    fn build_tiles(&mut self) {
+        let (width, height) = (self.board.width, self.board.height);
+        self.tiles_x = width.div_ceil(self.tile_size);
+        self.tiles_y = height.div_ceil(self.tile_size);
+        let tiles = self.tiles_x * self.tiles_y;
+        let topology = self.board.topology();
+        let r = self.board.rule().neighborhood().radius() as isize;
+        self.neighbors = (0..tiles)
+            .map(|tile| {
+                let x0 = (tile % self.tiles_x * self.tile_size) as isize;
+                let y0 = (tile / self.tiles_x * self.tile_size) as isize;
+                let x1 = usize::min(x0 as usize + self.tile_size, width) as isize;
+                let y1 = usize::min(y0 as usize + self.tile_size, height) as isize;
+                // The band of cells within the rule's reach outside the tile, plus the tile
+                // itself.
+                let top_bottom = (x0 - r..x1 + r)
+                    .flat_map(|x| (1..=r).flat_map(move |d| [(x, y0 - d), (x, y1 - 1 + d)]));
+                let sides = (y0..y1)
+                    .flat_map(|y| (1..=r).flat_map(move |d| [(x0 - d, y), (x1 - 1 + d, y)]));
+                let mut neighbors: Vec<usize> = top_bottom
+                    .chain(sides)
+                    .filter_map(|(x, y)| topology.wrap(x, y, width, height))
+                    .map(|(x, y)| self.tile_of(x, y))
+                    .chain([tile])
+                    .collect();
+                neighbors.sort_unstable();
+                neighbors.dedup();
+                neighbors
+            })
+            .collect();
+        self.changed = vec![true; tiles];
+        self.next_changed = vec![false; tiles];
+    }
+}
+
+impl From<GameOfLife> for ActiveLife {
+    fn from(value: GameOfLife) -> Self {
+        ActiveLife::new(value)
+    }
+}
+
+impl Read for ActiveLife {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        self.board.read_cell(x, y)
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        self.board.read_neighbors(x, y)
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        self.board.read_neighborhood(x, y, neighborhood)
+    }
+
+    fn width(&self) -> usize {
+        self.board.width
+    }
+
+    fn height(&self) -> usize {
+        self.board.height
+    }
+}
+
+impl Write for ActiveLife {
+    /// Sets the cell, and marks its tile as changed so the area around it is recomputed.
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        self.board.write_cell(x, y, alive)?;
+        let tile = self.tile_of(x, y);
+        self.changed[tile] = true;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    error::Error, game_of_life::GameOfLife, neighborhood::Neighborhood, read::Read as GoLRead,
+    rule::Rule, topology::Topology,
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A bit-packed in-memory representation of the Game of Life. Each cell takes up a single bit, and
+/// all rows are stored in one contiguous allocation of u64 words, with bit `x % 64` of word
+/// `x / 64` holding the cell at column `x`.
+///
+/// Transitions are computed 64 cells at a time using bitwise adders, making this considerably
+/// faster than [GameOfLife] for large boards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitBoard {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    cells: Vec<u64>,
+    rule: Rule,
+    topology: Topology,
+}
+
+impl BitBoard {
+    /// Creates a board of the provided size with every cell dead.
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD_BITS);
+        BitBoard {
+            width,
+            height,
+            words_per_row,
+            cells: vec![0; words_per_row * height],
+            rule: Rule::default(),
+            topology: Topology::default(),
+        }
+    }
+
+    /// Sets the ruleset used when transitioning the board. Defaults to [Rule::CONWAY].
+    pub fn with_rule(self, rule: Rule) -> Self {
+        Self { rule, ..self }
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the cell at the coordinates provided, or None if they are off the board.
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        if x < self.width && y < self.height {
+            Some(self.cells[self.word_index(x, y)] & (1 << (x % WORD_BITS)) != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the cell at the coordinates provided.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are off the board.
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        assert!(
+            x < self.width && y < self.height,
+            "Cell is not on the board"
+        );
+        let index = self.word_index(x, y);
+        if alive {
+            self.cells[index] |= 1 << (x % WORD_BITS);
+        } else {
+            self.cells[index] &= !(1 << (x % WORD_BITS));
+        }
+    }
+
+    /// Iterate over the cells of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get(x, y).expect("Coordinates are on the board"))
+    }
+
+    /// The number of live cells on the board.
+    pub fn population(&self) -> usize {
+        self.cells.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Run the ruleset against the board and create the next iteration.
+    ///
+    /// Only the Moore neighborhood is computed word-parallel. Rules with other neighborhoods are
+    /// computed one cell at a time.
+    pub fn transition(&self) -> Self {
+        if self.rule.neighborhood() != Neighborhood::Moore(1) {
+            return self.transition_cells();
+        }
+        let mut next = vec![0; self.cells.len()];
+        let empty = vec![0; self.words_per_row];
+        for y in 0..self.height {
+            let above = if y == 0 { &empty } else { self.row(y - 1) };
+            let below = if y + 1 == self.height {
+                &empty
+            } else {
+                self.row(y + 1)
+            };
+            let row = self.row(y);
+            let out = &mut next[y * self.words_per_row..(y + 1) * self.words_per_row];
+            for (i, word) in out.iter_mut().enumerate() {
+                let neighbors = [
+                    west(above, i),
+                    above[i],
+                    east(above, i),
+                    west(row, i),
+                    east(row, i),
+                    west(below, i),
+                    below[i],
+                    east(below, i),
+                ];
+                *word = self.apply_rule(row[i], count_neighbors(neighbors));
+            }
+            if let Some(last) = out.last_mut() {
+                *last &= self.last_word_mask();
+            }
+        }
+        let mut next = Self {
+            cells: next,
+            ..self.clone()
+        };
+        if self.topology != Topology::Bounded && !self.cells.is_empty() {
+            self.fix_edges(&mut next);
+        }
+        next
+    }
+
+    fn transition_cells(&self) -> Self {
+        let mut next = Self {
+            cells: vec![0; self.cells.len()],
+            ..self.clone()
+        };
+        let neighborhood = self.rule.neighborhood();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let alive = self
+                    .topology
+                    .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+                    .filter(|coord| coord.and_then(|(x, y)| self.get(x, y)) == Some(true))
+                    .count();
+                let current = self.get(x, y).expect("Cell is on the board");
+                next.set(x, y, self.rule.next_state(current, alive));
+            }
+        }
+        next
+    }
+
+    /// Applies the rule to 64 cells at once. `counts` holds the bit planes of each cell's neighbor
+    /// count, least significant first.
+    fn apply_rule(&self, cells: u64, counts: [u64; 4]) -> u64 {
+        let mut result = 0;
+        for n in 0..=8 {
+            let birth = self.rule.is_birth(n);
+            let survival = self.rule.is_survival(n);
+            if !birth && !survival {
+                continue;
+            }
+            let matches = (0..4).fold(u64::MAX, |acc, bit| {
+                acc & if n & (1 << bit) != 0 {
+                    counts[bit]
+                } else {
+                    !counts[bit]
+                }
+            });
+            let applies = match (birth, survival) {
+                (true, true) => u64::MAX,
+                (true, false) => !cells,
+                _ => cells,
+            };
+            result |= matches & applies;
+        }
+        result
+    }
+
+    /// The word-parallel transition treats everything past the edges as dead. For wrapping
+    /// topologies, the cells along the border are recomputed one at a time.
+    fn fix_edges(&self, next: &mut Self) {
+        let (w, h) = (self.width, self.height);
+        let border = (0..w)
+            .flat_map(|x| [(x, 0), (x, h - 1)])
+            .chain((0..h).flat_map(|y| [(0, y), (w - 1, y)]));
+        for (x, y) in border {
+            let alive = self.get(x, y).expect("Border cells are on the board");
+            let neighbors = self.neighbors(x, y);
+            next.set(x, y, self.rule.is_alive(alive, &neighbors));
+        }
+    }
+
+    fn neighbors(&self, x: usize, y: usize) -> [Option<bool>; 8] {
+        self.topology
+            .neighbor_coordinates(x, y, self.width, self.height)
+            .map(|coord| coord.and_then(|(x, y)| self.get(x, y)))
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.cells[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    fn word_index(&self, x: usize, y: usize) -> usize {
+        y * self.words_per_row + x / WORD_BITS
+    }
+
+    fn last_word_mask(&self) -> u64 {
+        match self.width % WORD_BITS {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+}
+
+/// The word whose bit `x` holds the cell at `x - 1`.
+fn west(row: &[u64], i: usize) -> u64 {
+    let carry = if i == 0 { 0 } else { row[i - 1] >> 63 };
+    (row[i] << 1) | carry
+}
+
+/// The word whose bit `x` holds the cell at `x + 1`.
+fn east(row: &[u64], i: usize) -> u64 {
+    let carry = row.get(i + 1).map(|w| w << 63).unwrap_or(0);
+    (row[i] >> 1) | carry
+}
+
+fn half_add(a: u64, b: u64) -> (u64, u64) {
+    (a ^ b, a & b)
+}
+
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let (sum, carry) = half_add(a, b);
+    (sum ^ c, carry | (sum & c))
+}
+
+/// Sums eight neighbor words into the four bit planes of a 0 to 8 count.
+fn count_neighbors(n: [u64; 8]) -> [u64; 4] {
+    let (sum_a, carry_a) = full_add(n[0], n[1], n[2]);
+    let (sum_b, carry_b) = full_add(n[3], n[4], n[5]);
+    let (sum_c, carry_c) = half_add(n[6], n[7]);
+    let (ones, carry_ones) = full_add(sum_a, sum_b, sum_c);
+    let (twos_partial, fours_a) = full_add(carry_a, carry_b, carry_c);
+    let (twos, fours_b) = half_add(twos_partial, carry_ones);
+    let (fours, eights) = half_add(fours_a, fours_b);
+    [ones, twos, fours, eights]
+}
+
+impl From<&GameOfLife> for BitBoard {
+    fn from(value: &GameOfLife) -> Self {
+        let mut board = BitBoard::new(value.width(), value.height())
+            .with_rule(value.rule())
+            .with_topology(value.topology());
+        let width = board.width.max(1);
+        for (i, alive) in value.iter().enumerate() {
+            if alive {
+                board.set(i % width, i / width, true);
+            }
+        }
+        board
+    }
+}
+
+impl From<&BitBoard> for GameOfLife {
+    fn from(value: &BitBoard) -> Self {
+        let rows: Vec<Vec<bool>> = (0..value.height)
+            .map(|y| (0..value.width).map(|x| value.get(x, y).unwrap()).collect())
+            .collect();
+        GameOfLife::from(rows)
+            .with_rule(value.rule)
+            .with_topology(value.topology)
+    }
+}
+
+impl From<Vec<Vec<bool>>> for BitBoard {
+    fn from(value: Vec<Vec<bool>>) -> Self {
+        (&GameOfLife::from(value)).into()
+    }
+}
+
+impl GoLRead for BitBoard {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        self.get(x, y).ok_or(Error::OutOfBounds { x, y })
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self.neighbors(x, y))
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self
+            .topology
+            .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+            .map(|coord| coord.and_then(|(x, y)| self.get(x, y)))
+            .collect())
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+}
@@ -0,0 +1,309 @@
+//! # Object Census
+//! Once a soup has settled down, what's left is usually a scattering of small, well known
+//! objects: blocks, blinkers, beehives, gliders heading off into the distance. A [Census] splits
+//! a board into its connected objects and names each one, much like apgsearch does.
+//!
+//! Objects are named by their apgcode. Each object is run on its own until it repeats, which
+//! gives its prefix: `xs` and the population for still lifes, `xp` and the period for
+//! oscillators and `xq` and the period for spaceships. The rest of the code is the object's
+//! cells in extended Wechsler format, taken from whichever phase and orientation gives the
+//! shortest code, with ties going to the code that comes first alphabetically. The same object
+//! always gets the same code, however it is turned or reflected and whichever phase it is in.
+//!
+//! In extended Wechsler format the cells are cut into strips 5 rows tall, separated by `z`. Each
+//! column of a strip becomes a digit from `0` to `v`, counting the top cell as 1, the next as 2
+//! and so on. Trailing zeros in a strip are dropped and runs of zeros are shortened, `w` for two
+//! zeros, `x` for three and `y` followed by a digit for four or more.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    cycle::{Cycle, CycleDetector},
+    game_of_life::GameOfLife,
+};
+
+/// The common objects [Census] recognises by name, by their apgcodes.
+pub const CATALOG: &[(&str, &str)] = &[
+    ("xs4_33", "block"),
+    ("xs4_252", "tub"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs6_696", "beehive"),
+    ("xs6_25a4", "barge"),
+    ("xs7_2596", "loaf"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+];
+
+/// The key [Census::count] uses for objects that couldn't be classified.
+pub const UNKNOWN: &str = "unknown";
+
+/// The digits for columns, which only go up to `v`, and for long runs of zeros after a `y`.
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// The name of a common object, looked up in the [CATALOG] by its apgcode.
+pub fn name(apgcode: &str) -> Option<&'static str> {
+    CATALOG
+        .iter()
+        .find(|(code, _)| *code == apgcode)
+        .map(|(_, name)| *name)
+}
+
+/// A connected object found on a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    cells: Vec<(usize, usize)>,
+    apgcode: Option<String>,
+}
+
+impl Object {
+    /// The object's live cells, as they are on the board.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    /// The object's apgcode, or None if it didn't repeat within the census's period limit when
+    /// run on its own, such as a still active reaction.
+    pub fn apgcode(&self) -> Option<&str> {
+        self.apgcode.as_deref()
+    }
+
+    /// The object's name, if it is in the [CATALOG].
+    pub fn name(&self) -> Option<&'static str> {
+        self.apgcode().and_then(name)
+    }
+}
+
+/// Splits boards into connected objects and classifies them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Census {
+    distance: usize,
+    max_period: u64,
+}
+
+impl Census {
+    /// Creates a census that joins cells touching each other, including diagonally, and finds
+    /// periods of up to 64 generations.
+    pub fn new() -> Self {
+        Census {
+            distance: 1,
+            max_period: 64,
+        }
+    }
+
+    /// Sets how many cells apart, horizontally, vertically or diagonally, two live cells can be
+    /// and still belong to the same object. Defaults to 1, so only touching cells are joined.
+    pub fn with_distance(self, distance: usize) -> Self {
+        Self {
+            distance: distance.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the longest period an object can have and still be classified. Defaults to 64.
+    pub fn with_max_period(self, max_period: u64) -> Self {
+        Self { max_period, ..self }
+    }
+
+    /// Splits a board into its objects, classifying each one under the board's rule. The board
+    /// is treated as bounded whatever its topology. Objects are ordered by their topmost, then
+    /// leftmost, cell.
+    pub fn take(&self, board: &GameOfLife) -> Vec<Object> {
+        self.components(board)
+            .into_iter()
+            .map(|cells| Object {
+                apgcode: self.apgcode(board, &cells),
+                cells,
+            })
+            .collect()
+    }
+
+    /// Counts how many of each object are on a board, by apgcode. Objects that couldn't be
+    /// classified are counted under [UNKNOWN].
+    pub fn count(&self, board: &GameOfLife) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for object in self.take(board) {
+            let code = object.apgcode.unwrap_or_else(|| UNKNOWN.to_string());
+            *counts.entry(code).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Groups the live cells of a board into objects, joining cells within the census's distance
+    /// of each other.
+    pub fn components(&self, board: &GameOfLife) -> Vec<Vec<(usize, usize)>> {
+        let (width, height) = (board.width, board.height);
+        let mut seen = vec![false; board.cells.len()];
+        let mut components = Vec::new();
+        for start in 0..board.cells.len() {
+            if !board.cells[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut cells = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            while let Some(i) = queue.pop_front() {
+                let (x, y) = (i % width, i / width);
+                cells.push((x, y));
+                let xs = x.saturating_sub(self.distance)..(x + self.distance + 1).min(width);
+                for ny in y.saturating_sub(self.distance)..(y + self.distance + 1).min(height) {
+                    for nx in xs.clone() {
+                        let j = ny * width + nx;
+                        if board.cells[j] && !seen[j] {
+                            seen[j] = true;
+                            queue.push_back(j);
+                        }
+                    }
+                }
+            }
+            cells.sort_by_key(|(x, y)| (*y, *x));
+            components.push(cells);
+        }
+        components
+    }
+
+    /// Runs an object on its own board until it repeats, then encodes every phase in every
+    /// orientation and keeps the best code.
+    fn apgcode(&self, board: &GameOfLife, cells: &[(usize, usize)]) -> Option<String> {
+        let min_x = cells.iter().map(|(x, _)| *x).min()?;
+        let min_y = cells.iter().map(|(_, y)| *y).min()?;
+        let max_x = cells.iter().map(|(x, _)| *x).max()?;
+        let max_y = cells.iter().map(|(_, y)| *y).max()?;
+        // Nothing can travel further than the radius of the neighborhood in a generation, so this
+        // much room keeps the object clear of the edges for as long as it is run.
+        let radius = usize::from(board.rule().neighborhood().radius()).max(1);
+        let padding = (self.max_period as usize + 1) * radius + 1;
+        let mut rows =
+            vec![vec![false; max_x - min_x + 1 + 2 * padding]; max_y - min_y + 1 + 2 * padding];
+        for (x, y) in cells {
+            rows[y - min_y + padding][x - min_x + padding] = true;
+        }
+        let mut isolated = GameOfLife::from(rows).with_rule(board.rule());
+
+        let mut detector = CycleDetector::new().with_generation_limit(self.max_period);
+        let (prefix, period) = match detector.run(&mut isolated) {
+            Cycle::Stable => ("xs", 1),
+            Cycle::Oscillating { period } => ("xp", period),
+            Cycle::Translating { period, .. } => ("xq", period),
+            Cycle::Unknown => return None,
+        };
+        // An object that only repeats after changing into something else isn't periodic itself.
+        if detector.generation() != period + 1 {
+            return None;
+        }
+
+        let mut best: Option<String> = None;
+        for _ in 0..period {
+            let phase: Vec<(isize, isize)> = isolated
+                .iter()
+                .enumerate()
+                .filter(|(_, alive)| *alive)
+                .map(|(i, _)| ((i % isolated.width) as isize, (i / isolated.width) as isize))
+                .collect();
+            for orientation in 0..8 {
+                let code = wechsler(&orient(&phase, orientation));
+                if best
+                    .as_ref()
+                    .is_none_or(|best| (code.len(), &code) < (best.len(), best))
+                {
+                    best = Some(code);
+                }
+            }
+            isolated.step();
+        }
+        let suffix = match prefix {
+            "xs" => cells.len() as u64,
+            _ => period,
+        };
+        Some(format!("{prefix}{suffix}_{}", best?))
+    }
+}
+
+impl Default for Census {
+    fn default() -> Self {
+        Census::new()
+    }
+}
+
+/// Turns and reflects cells into one of the 8 orientations, moved so their bounding box starts
+/// at (0, 0).
+fn orient(cells: &[(isize, isize)], orientation: u8) -> Vec<(usize, usize)> {
+    let turned: Vec<(isize, isize)> = cells
+        .iter()
+        .map(|(x, y)| {
+            let (x, y) = if orientation & 4 != 0 {
+                (*y, *x)
+            } else {
+                (*x, *y)
+            };
+            let x = if orientation & 1 != 0 { -x } else { x };
+            let y = if orientation & 2 != 0 { -y } else { y };
+            (x, y)
+        })
+        .collect();
+    let min_x = turned.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let min_y = turned.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    turned
+        .into_iter()
+        .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+        .collect()
+}
+
+/// Encodes cells whose bounding box starts at (0, 0) in extended Wechsler format.
+fn wechsler(cells: &[(usize, usize)]) -> String {
+    let width = cells.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut strips = vec![vec![0u8; width]; height.div_ceil(5)];
+    for (x, y) in cells {
+        strips[y / 5][*x] |= 1 << (y % 5);
+    }
+    let strips: Vec<String> = strips
+        .iter()
+        .map(|strip| {
+            let len = strip
+                .iter()
+                .rposition(|column| *column != 0)
+                .map_or(0, |i| i + 1);
+            compress_zeros(&strip[..len])
+        })
+        .collect();
+    strips.join("z")
+}
+
+/// Writes the digits of a strip, shortening runs of zeros.
+fn compress_zeros(columns: &[u8]) -> String {
+    let mut code = String::new();
+    let mut zeros = 0;
+    for column in columns {
+        if *column == 0 {
+            zeros += 1;
+        } else {
+            push_zeros(&mut code, zeros);
+            zeros = 0;
+            code.push(char::from(DIGITS[usize::from(*column)]));
+        }
+    }
+    push_zeros(&mut code, zeros);
+    code
+}
+
+/// Writes a run of zeros in as few characters as possible.
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = zeros.min(39);
+        match run {
+            1 => code.push('0'),
+            2 => code.push('w'),
+            3 => code.push('x'),
+            _ => {
+                code.push('y');
+                code.push(char::from(DIGITS[run - 4]));
+            }
+        }
+        zeros -= run;
+    }
+}
@@ -0,0 +1,180 @@
+//! # Cycle Detection
+//! A soup is run until it settles down, which on a finite board it always does eventually: every
+//! generation is one of finitely many, so the board has to return to one it has been in before.
+//! From then on it repeats with a fixed period. [CycleDetector] hashes each generation and
+//! watches for a repeat.
+//!
+//! Comparing whole boards only finds patterns that return to the same place. When the detector
+//! is translation-invariant, each generation is hashed as it looks inside its bounding box
+//! instead, so spaceships are recognised as well, along with how far they move each period.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use crate::game_of_life::GameOfLife;
+
+/// How a board behaves once it has settled down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cycle {
+    /// The board stopped changing, including when every cell died.
+    Stable,
+    /// The board returns to the same generation every `period` generations.
+    Oscillating {
+        /// The number of generations before the board repeats.
+        period: u64,
+    },
+    /// The board returns to the same generation every `period` generations, moved `dx` cells to
+    /// the right and `dy` cells down.
+    Translating {
+        /// The number of generations before the board repeats.
+        period: u64,
+        /// How far the board moves to the right each period.
+        dx: isize,
+        /// How far the board moves down each period.
+        dy: isize,
+    },
+    /// No repeat was found before the generation limit.
+    Unknown,
+}
+
+/// Where a generation was seen.
+#[derive(Debug, Clone, Copy)]
+struct Sighting {
+    generation: u64,
+    origin: (usize, usize),
+}
+
+/// Finds the period a board settles into by hashing each of its generations.
+///
+/// Only the hashes of the most recent generations are kept, up to the history size, so cycles
+/// with a longer period than that go unnoticed. Generations are compared by their 64 bit hashes
+/// alone, so a collision could in principle report a cycle too early.
+#[derive(Debug, Clone)]
+pub struct CycleDetector {
+    translation_invariant: bool,
+    history: usize,
+    limit: u64,
+    generation: u64,
+    seen: HashMap<u64, Sighting>,
+    order: VecDeque<u64>,
+}
+
+impl CycleDetector {
+    /// Creates a translation-invariant detector that keeps 4096 generations of history and gives
+    /// up after 100,000 generations.
+    pub fn new() -> Self {
+        CycleDetector {
+            translation_invariant: true,
+            history: 4096,
+            limit: 100_000,
+            generation: 0,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Sets whether generations are compared inside their bounding boxes, so moving patterns
+    /// repeat too. Defaults to true.
+    pub fn with_translation_invariance(self, translation_invariant: bool) -> Self {
+        Self {
+            translation_invariant,
+            ..self
+        }
+    }
+
+    /// Sets how many of the most recent generations are remembered, which is the longest period
+    /// that can be found. Defaults to 4096.
+    pub fn with_history(self, history: usize) -> Self {
+        Self {
+            history: history.max(1),
+            ..self
+        }
+    }
+
+    /// Sets how many generations [CycleDetector::run] steps a board before giving up. Defaults
+    /// to 100,000.
+    pub fn with_generation_limit(self, limit: u64) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// The number of generations observed so far.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Forgets every generation observed so far, to start on another board.
+    pub fn reset(&mut self) {
+        self.generation = 0;
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    /// Records the next generation of a board that is being run elsewhere, returning how it
+    /// cycles if it matches a generation in the history.
+    pub fn observe(&mut self, board: &GameOfLife) -> Option<Cycle> {
+        let (hash, origin) = self.hash(board);
+        let generation = self.generation;
+        self.generation += 1;
+        if let Some(earlier) = self.seen.get(&hash) {
+            let period = generation - earlier.generation;
+            let dx = origin.0 as isize - earlier.origin.0 as isize;
+            let dy = origin.1 as isize - earlier.origin.1 as isize;
+            return Some(match (period, dx, dy) {
+                (1, 0, 0) => Cycle::Stable,
+                (period, 0, 0) => Cycle::Oscillating { period },
+                (period, dx, dy) => Cycle::Translating { period, dx, dy },
+            });
+        }
+        if self.order.len() == self.history {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(hash, Sighting { generation, origin });
+        self.order.push_back(hash);
+        None
+    }
+
+    /// Steps the board in place until it repeats a generation, returning how it cycles, or
+    /// [Cycle::Unknown] if the generation limit is reached first. The board is left at the first
+    /// repeated generation.
+    pub fn run(&mut self, board: &mut GameOfLife) -> Cycle {
+        for _ in 0..=self.limit {
+            if let Some(cycle) = self.observe(board) {
+                return cycle;
+            }
+            board.step();
+        }
+        Cycle::Unknown
+    }
+
+    /// Hashes a generation, along with where the hashed cells start on the board.
+    fn hash(&self, board: &GameOfLife) -> (u64, (usize, usize)) {
+        let mut hasher = DefaultHasher::new();
+        let origin = if self.translation_invariant {
+            match board.bounding_box() {
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (max_x - min_x, max_y - min_y).hash(&mut hasher);
+                    for row in board.cells.chunks(board.width).take(max_y + 1).skip(min_y) {
+                        row[min_x..=max_x].hash(&mut hasher);
+                    }
+                    (min_x, min_y)
+                }
+                None => (0, 0),
+            }
+        } else {
+            board.width.hash(&mut hasher);
+            board.cells.hash(&mut hasher);
+            (0, 0)
+        };
+        (hasher.finish(), origin)
+    }
+}
+
+impl Default for CycleDetector {
+    fn default() -> Self {
+        CycleDetector::new()
+    }
+}
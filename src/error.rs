@@ -0,0 +1,74 @@
+use std::{error, fmt, io};
+
+/// The errors that can occur while reading, writing or running a board.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The data doesn't start with the magic bytes of the expected format.
+    BadMagic,
+    /// The data ended before the header was complete.
+    TruncatedHeader,
+    /// The data ended before all of the cells, with the number of bytes expected and the number
+    /// actually available.
+    TruncatedBody { expected: usize, got: usize },
+    /// The coordinates aren't on the board.
+    OutOfBounds { x: usize, y: usize },
+    /// The number of cells provided doesn't match the dimensions of the board, or two boards that
+    /// must be the same size aren't.
+    DimensionMismatch,
+    /// The file is a version of the format that isn't supported.
+    UnsupportedVersion(u8),
+    /// The data or board uses a feature that isn't supported.
+    Unsupported(&'static str),
+    /// A rulestring couldn't be parsed.
+    InvalidRule(&'static str),
+    /// The data isn't valid for its format.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::BadMagic => write!(f, "Data isn't in the expected format"),
+            Error::TruncatedHeader => write!(f, "Header ended unexpectedly"),
+            Error::TruncatedBody { expected, got } => {
+                write!(
+                    f,
+                    "Expected {expected} bytes of cells, but only {got} were present"
+                )
+            }
+            Error::OutOfBounds { x, y } => write!(f, "Cell ({x}, {y}) is not on the board"),
+            Error::DimensionMismatch => write!(f, "Dimensions of the board don't match"),
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported version {version}"),
+            Error::Unsupported(feature) => write!(f, "Unsupported: {feature}"),
+            Error::InvalidRule(reason) => write!(f, "Invalid rule: {reason}"),
+            Error::Malformed(reason) => write!(f, "Malformed data: {reason}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+/// Maps an error from reading a header, treating the data ending early as a truncated header.
+pub(crate) fn header_error(e: io::Error) -> Error {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        Error::TruncatedHeader
+    } else {
+        Error::Io(e)
+    }
+}
@@ -1,109 +1,247 @@
-use std::{
-    io::{Read as IORead, Seek},
-    iter::Flatten,
-};
+use std::io::{Read as IORead, Seek};
 
 use crate::{
-    io::GoLFile,
-    read::Read as GoLRead,
-    utils::{is_alive, neighbor_coordinates},
+    error::Error, io::GoLFile, neighborhood::Neighborhood, read::Read as GoLRead, rule::Rule,
+    topology::Topology, write::Write as GoLWrite,
 };
 
 /// An in-memory representation of Conway's Game of Life as a fixed size board with dead and alive
 /// cells.
 ///
+/// The board keeps a second buffer of the same size to compute the next generation into, so
+/// [GameOfLife::step] never allocates.
+///
 /// For a memory efficient equivalent that uses the file system, see [GoLFile].
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct GameOfLife {
-    width: usize,
-    height: usize,
-    inner: Vec<Vec<bool>>,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) cells: Vec<bool>,
+    pub(crate) scratch: Vec<bool>,
+    /// The offsets of the cells counted by the rule's neighborhood, worked out when the rule is
+    /// set rather than once per cell.
+    offsets: Vec<(isize, isize)>,
+    rule: Rule,
+    topology: Topology,
 }
 
 impl GameOfLife {
-    /// Run the ruleset against the board and create the next iteration.
-    pub fn transition(&self) -> Self {
-        let mut next = Vec::with_capacity(self.height);
-        for y in 0..self.height {
-            let mut row = Vec::with_capacity(self.width);
-            for x in 0..self.width {
-                let cell = self.inner_read_cell(x, y);
-                let neighbors = self.inner_read_neighbors(x, y);
-                row.push(is_alive(cell, &neighbors));
-            }
-            next.push(row);
-        }
+    /// Sets the ruleset used when transitioning the board. Defaults to [Rule::CONWAY].
+    pub fn with_rule(self, rule: Rule) -> Self {
         Self {
-            inner: next,
-            ..*self
+            offsets: rule.neighborhood().offsets().collect(),
+            rule,
+            ..self
+        }
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Advance the board a single generation in place.
+    pub fn step(&mut self) {
+        let mut next = std::mem::take(&mut self.scratch);
+        for (i, cell) in next.iter_mut().enumerate() {
+            *cell = self.next_cell(i % self.width, i / self.width);
+        }
+        self.scratch = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
         }
     }
 
+    /// Run the ruleset against the board and create the next iteration. Prefer
+    /// [GameOfLife::step] when the current generation is no longer needed, since it doesn't
+    /// allocate.
+    pub fn transition(&self) -> Self {
+        let mut next = self.clone();
+        next.step();
+        next
+    }
+
+    /// Run the ruleset against the board and create the next iteration, splitting the board into
+    /// horizontal bands that are processed on up to `threads` threads at once. Each band reads the
+    /// row on either side of it from its neighbors, and the result is identical to
+    /// [GameOfLife::transition].
+    ///
+    /// [std::thread::available_parallelism] is a good default for the number of threads.
+    pub fn transition_parallel(&self, threads: usize) -> Self {
+        let mut next = self.clone();
+        let band_height = self.height.div_ceil(threads.max(1)).max(1);
+        let band_cells = (band_height * self.width).max(1);
+        std::thread::scope(|scope| {
+            for (band, cells) in next.cells.chunks_mut(band_cells).enumerate() {
+                scope.spawn(move || {
+                    let start = band * band_cells;
+                    for (i, cell) in cells.iter_mut().enumerate() {
+                        let i = start + i;
+                        *cell = self.next_cell(i % self.width, i / self.width);
+                    }
+                });
+            }
+        });
+        next
+    }
+
     /// Iterate over the cells of the board, from (0, 0) to `(self.width(), self.height())`
     pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
-        self.inner.iter().flatten().copied()
+        self.cells.iter().copied()
+    }
+
+    /// The smallest rectangle containing every live cell, as `(min_x, min_y, max_x, max_y)`
+    /// inclusive. Returns None if the board is empty.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        self.cells
+            .chunks(self.width.max(1))
+            .enumerate()
+            .filter_map(|(y, row)| {
+                let min_x = row.iter().position(|alive| *alive)?;
+                let max_x = row.iter().rposition(|alive| *alive)?;
+                Some((min_x, y, max_x))
+            })
+            .fold(None, |bounds, (x0, y, x1)| {
+                Some(match bounds {
+                    None => (x0, y, x1, y),
+                    Some((min_x, min_y, max_x, _)) => (min_x.min(x0), min_y, max_x.max(x1), y),
+                })
+            })
+    }
+
+    /// The next state of a cell, counting its neighbors in the rule's neighborhood.
+    pub(crate) fn next_cell(&self, x: usize, y: usize) -> bool {
+        let alive = self
+            .offsets
+            .iter()
+            .filter_map(|(dx, dy)| {
+                self.topology
+                    .wrap(x as isize + dx, y as isize + dy, self.width, self.height)
+            })
+            .filter(|(x, y)| self.inner_read_cell(*x, *y))
+            .count();
+        self.rule.next_state(self.inner_read_cell(x, y), alive)
     }
 
     fn inner_read_cell(&self, x: usize, y: usize) -> bool {
-        self.inner[y][x]
+        self.cells[y * self.width + x]
     }
 
     fn inner_read_neighbors(&self, x: usize, y: usize) -> [Option<bool>; 8] {
-        neighbor_coordinates(x, y).map(|b_coord| {
-            b_coord.and_then(|coord| {
-                self.inner
-                    .get(coord.1)
-                    .and_then(|column| column.get(coord.0))
-                    .cloned()
-            })
-        })
+        self.topology
+            .neighbor_coordinates(x, y, self.width, self.height)
+            .map(|b_coord| b_coord.map(|(x, y)| self.inner_read_cell(x, y)))
+    }
+}
+
+impl PartialEq for GameOfLife {
+    /// Boards are equal when they have the same size, cells, rule and topology.
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.cells == other.cells
+            && self.rule == other.rule
+            && self.topology == other.topology
     }
 }
 
+impl Eq for GameOfLife {}
+
 impl From<Vec<Vec<bool>>> for GameOfLife {
+    /// Builds a board from rows of cells. The width is taken from the first row, and any other
+    /// rows are padded with dead cells or cut to match.
     fn from(value: Vec<Vec<bool>>) -> Self {
-        let width = value.get(0).map(|row| row.len()).unwrap_or(0);
+        let width = value.first().map(|row| row.len()).unwrap_or(0);
         let height = value.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for row in value {
+            let len = cells.len();
+            cells.extend(row.into_iter().take(width));
+            cells.resize(len + width, false);
+        }
         GameOfLife {
-            inner: value,
+            scratch: vec![false; cells.len()],
+            cells,
             width,
             height,
+            offsets: Rule::default().neighborhood().offsets().collect(),
+            rule: Rule::default(),
+            topology: Topology::default(),
         }
     }
 }
 
 impl<R: IORead + Seek> TryFrom<GoLFile<R>> for GameOfLife {
-    type Error = &'static str;
+    type Error = Error;
 
-    fn try_from(value: GoLFile<R>) -> Result<Self, Self::Error> {
+    fn try_from(mut value: GoLFile<R>) -> Result<Self, Self::Error> {
         let width = value.width();
         let height = value.height();
-        let mut inner = Vec::with_capacity(height);
-        let mut iter = value.into_iter();
-        for _ in 0..height {
-            let mut buf = Vec::with_capacity(width);
-            for _ in 0..width {
-                buf.push(iter.next().ok_or("Iterator ended unexpectedly")?);
+        let topology = value.topology();
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(value.read_cell(x, y)?);
             }
-            inner.push(buf);
         }
         Ok(Self {
             width,
             height,
-            inner,
+            scratch: vec![false; cells.len()],
+            cells,
+            offsets: Rule::default().neighborhood().offsets().collect(),
+            rule: Rule::default(),
+            topology,
         })
     }
 }
 
 impl GoLRead for GameOfLife {
-    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
         Ok(self.inner_read_cell(x, y))
     }
 
-    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], &'static str> {
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
         Ok(self.inner_read_neighbors(x, y))
     }
 
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self
+            .topology
+            .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+            .map(|coord| coord.map(|(x, y)| self.inner_read_cell(x, y)))
+            .collect())
+    }
+
     fn width(&self) -> usize {
         self.width
     }
@@ -113,11 +251,25 @@ impl GoLRead for GameOfLife {
     }
 }
 
+impl GoLWrite for GameOfLife {
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        self.cells[y * self.width + x] = alive;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
 impl IntoIterator for GameOfLife {
-    type IntoIter = Flatten<std::vec::IntoIter<Vec<bool>>>;
+    type IntoIter = std::vec::IntoIter<bool>;
     type Item = bool;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.inner.into_iter().flatten()
+        self.cells.into_iter()
     }
 }
@@ -0,0 +1,268 @@
+//! # Generations Rules
+//! Generations rules extend life-like rules with refractory states. A live cell that doesn't
+//! survive doesn't die straight away, but instead ages through the dying states until it returns
+//! to dead. Dying cells don't count as live neighbors and can't be born into.
+//!
+//! Rules are written `B2/S/3`, with the total number of states (dead, alive and every dying
+//! state) last. The older S/B/C notation (`345/2/4`) is also accepted when parsing.
+//!
+//! States are numbered with 0 for dead, 1 for alive and 2 upwards for dying, so a board with up
+//! to 256 states fits in a `u8` per cell.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    error::Error, game_of_life::GameOfLife, grid::Grid, neighborhood::Neighborhood, read::Read,
+    rule::Rule, topology::Topology, write::Write,
+};
+
+/// A Generations ruleset, a life-like rule plus a number of states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Generations {
+    rule: Rule,
+    states: u16,
+}
+
+impl Generations {
+    /// Brian's Brain, `B2/S/3`.
+    pub const BRIANS_BRAIN: Generations = Generations {
+        rule: Rule::from_masks(0b100, 0),
+        states: 3,
+    };
+    /// Star Wars, `B2/S345/4`.
+    pub const STAR_WARS: Generations = Generations {
+        rule: Rule::from_masks(0b100, 0b11_1000),
+        states: 4,
+    };
+
+    /// Creates a ruleset from the life-like rule deciding births and survival, and the total
+    /// number of states, which must be between 2 and 256.
+    pub fn new(rule: Rule, states: u16) -> Result<Self, Error> {
+        if !(2..=256).contains(&states) {
+            return Err(Error::InvalidRule(
+                "Generations rules must have between 2 and 256 states",
+            ));
+        }
+        Ok(Generations { rule, states })
+    }
+
+    /// The life-like rule deciding births and survival.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// The total number of states, including dead and alive.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Returns the next state of a cell given its current state and number of live neighbors.
+    /// States past the last dying state are treated as dead.
+    pub fn next_state(&self, current: u8, alive_neighbors: usize) -> u8 {
+        match current {
+            dead if dead == 0 || u16::from(dead) >= self.states => {
+                u8::from(self.rule.is_birth(alive_neighbors))
+            }
+            1 if self.rule.is_survival(alive_neighbors) => 1,
+            dying => ((u16::from(dying) + 1) % self.states) as u8,
+        }
+    }
+}
+
+impl Default for Generations {
+    /// Conway's Game of Life, with no dying states.
+    fn default() -> Self {
+        Generations {
+            rule: Rule::CONWAY,
+            states: 2,
+        }
+    }
+}
+
+impl FromStr for Generations {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (rule, states) = s
+            .rsplit_once('/')
+            .ok_or(Error::InvalidRule("Generations rule is missing its states"))?;
+        let states = states.strip_prefix(['C', 'c']).unwrap_or(states);
+        let states = states
+            .parse()
+            .or(Err(Error::InvalidRule("Invalid number of states")))?;
+        Generations::new(rule.parse()?, states)
+    }
+}
+
+impl fmt::Display for Generations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.rule, self.states)
+    }
+}
+
+/// A fixed size board running a [Generations] rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationsLife {
+    grid: Grid<u8>,
+    rule: Generations,
+}
+
+impl GenerationsLife {
+    /// Creates an empty board running Conway's Game of Life.
+    pub fn new(width: usize, height: usize) -> Self {
+        Grid::new(width, height).into()
+    }
+
+    /// Sets the ruleset used when transitioning the board. Fails if any cell is in a state the
+    /// rule doesn't have.
+    pub fn with_rule(self, rule: Generations) -> Result<Self, Error> {
+        if self
+            .grid
+            .iter()
+            .any(|state| u16::from(state) >= rule.states)
+        {
+            return Err(Error::Unsupported("Cells in states the rule doesn't have"));
+        }
+        Ok(Self { rule, ..self })
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Generations {
+        self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self {
+            grid: self.grid.with_topology(topology),
+            ..self
+        }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.grid.topology()
+    }
+
+    /// The underlying board of cell states.
+    pub fn grid(&self) -> &Grid<u8> {
+        &self.grid
+    }
+
+    /// Iterate over the cell states of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.grid.iter()
+    }
+
+    /// The number of live cells on the board, not counting dying cells.
+    pub fn population(&self) -> usize {
+        self.grid.iter().filter(|state| *state == 1).count()
+    }
+
+    /// Advance the board a single generation in place.
+    pub fn step(&mut self) {
+        let rule = self.rule;
+        let neighborhood = rule.rule.neighborhood();
+        if neighborhood == Neighborhood::Moore(1) {
+            self.grid.step_with(|state, neighbors| {
+                let alive = neighbors.iter().filter(|n| **n == Some(1)).count();
+                rule.next_state(state, alive)
+            });
+        } else {
+            self.grid
+                .step_with_neighborhood(neighborhood, |state, neighbors| {
+                    let alive = neighbors.iter().filter(|n| **n == Some(1)).count();
+                    rule.next_state(state, alive)
+                });
+        }
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Run the ruleset against the board and create the next iteration.
+    pub fn transition(&self) -> Self {
+        let mut next = self.clone();
+        next.step();
+        next
+    }
+}
+
+impl From<Grid<u8>> for GenerationsLife {
+    /// Wraps a board of cell states, running Conway's Game of Life. Any cell in a state above
+    /// alive is treated as dead until a rule with more states is set.
+    fn from(value: Grid<u8>) -> Self {
+        GenerationsLife {
+            grid: value,
+            rule: Generations::default(),
+        }
+    }
+}
+
+impl From<&GameOfLife> for GenerationsLife {
+    /// Copies the board, with live cells in state 1 and the board's rule with no dying states.
+    fn from(value: &GameOfLife) -> Self {
+        let width = value.width();
+        let mut grid = Grid::new(width, value.height()).with_topology(value.topology());
+        for (i, alive) in value.iter().enumerate() {
+            if alive {
+                grid.write_cell(i % width, i / width, 1)
+                    .expect("Cell is on the board");
+            }
+        }
+        GenerationsLife {
+            grid,
+            rule: Generations {
+                rule: value.rule(),
+                states: 2,
+            },
+        }
+    }
+}
+
+impl Read<u8> for GenerationsLife {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<u8, Error> {
+        self.grid.read_cell(x, y)
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<u8>; 8], Error> {
+        self.grid.read_neighbors(x, y)
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<u8>>, Error> {
+        self.grid.read_neighborhood(x, y, neighborhood)
+    }
+
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+}
+
+impl Write<u8> for GenerationsLife {
+    /// Sets the state of a cell. Fails if the rule doesn't have that state.
+    fn write_cell(&mut self, x: usize, y: usize, state: u8) -> Result<(), Error> {
+        if u16::from(state) >= self.rule.states {
+            return Err(Error::Unsupported("Cells in states the rule doesn't have"));
+        }
+        self.grid.write_cell(x, y, state)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
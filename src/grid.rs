@@ -0,0 +1,171 @@
+use crate::{
+    error::Error, neighborhood::Neighborhood, read::Read, topology::Topology, write::Write,
+};
+
+/// A fixed size board where every cell holds a state of type `C`, for rules with more than two
+/// states. The default value of `C` is the empty state.
+///
+/// Like [GameOfLife](crate::game_of_life::GameOfLife), a second buffer is kept to compute the
+/// next generation into, so [Grid::step_with] never allocates.
+#[derive(Debug, Clone)]
+pub struct Grid<C> {
+    width: usize,
+    height: usize,
+    cells: Vec<C>,
+    scratch: Vec<C>,
+    topology: Topology,
+}
+
+impl<C: Copy + Default> Grid<C> {
+    /// Creates a board with every cell in the empty state.
+    pub fn new(width: usize, height: usize) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![C::default(); width * height],
+            scratch: vec![C::default(); width * height],
+            topology: Topology::default(),
+        }
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the cell at the coordinates provided, or None if they're off the board.
+    pub fn get(&self, x: usize, y: usize) -> Option<C> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Iterate over the cells of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = C> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// The eight cells surrounding the provided cell, clockwise from the top left. Cells off the
+    /// board are None.
+    pub fn neighbors(&self, x: usize, y: usize) -> [Option<C>; 8] {
+        self.topology
+            .neighbor_coordinates(x, y, self.width, self.height)
+            .map(|coord| coord.map(|(x, y)| self.cells[y * self.width + x]))
+    }
+
+    /// Advance the board a single generation in place, computing each cell's next state from its
+    /// current state and its neighbors.
+    pub fn step_with(&mut self, mut rule: impl FnMut(C, [Option<C>; 8]) -> C) {
+        let mut next = std::mem::take(&mut self.scratch);
+        for (i, cell) in next.iter_mut().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            *cell = rule(self.cells[i], self.neighbors(x, y));
+        }
+        self.scratch = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// Like [Grid::step_with], but passing the cells of `neighborhood` in the order of
+    /// [Neighborhood::offsets] rather than the eight surrounding cells.
+    pub fn step_with_neighborhood(
+        &mut self,
+        neighborhood: Neighborhood,
+        mut rule: impl FnMut(C, &[Option<C>]) -> C,
+    ) {
+        let offsets: Vec<_> = neighborhood.offsets().collect();
+        let mut neighbors = Vec::with_capacity(offsets.len());
+        let mut next = std::mem::take(&mut self.scratch);
+        for (i, cell) in next.iter_mut().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            neighbors.clear();
+            neighbors.extend(offsets.iter().map(|(dx, dy)| {
+                self.topology
+                    .wrap(x as isize + dx, y as isize + dy, self.width, self.height)
+                    .map(|(x, y)| self.cells[y * self.width + x])
+            }));
+            *cell = rule(self.cells[i], &neighbors);
+        }
+        self.scratch = std::mem::replace(&mut self.cells, next);
+    }
+}
+
+impl<C: PartialEq> PartialEq for Grid<C> {
+    /// Boards are equal when they have the same size, cells and topology.
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.cells == other.cells
+            && self.topology == other.topology
+    }
+}
+
+impl<C: Eq> Eq for Grid<C> {}
+
+impl<C: Copy + Default> From<Vec<Vec<C>>> for Grid<C> {
+    /// Builds a board from rows of cells. The width is taken from the first row, and any other
+    /// rows are padded with empty cells or cut to match.
+    fn from(value: Vec<Vec<C>>) -> Self {
+        let width = value.first().map(|row| row.len()).unwrap_or(0);
+        let mut grid = Grid::new(width, value.len());
+        for (y, row) in value.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().take(width).enumerate() {
+                grid.cells[y * width + x] = cell;
+            }
+        }
+        grid
+    }
+}
+
+impl<C: Copy + Default> Read<C> for Grid<C> {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<C, Error> {
+        self.get(x, y).ok_or(Error::OutOfBounds { x, y })
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<C>; 8], Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self.neighbors(x, y))
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<C>>, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self
+            .topology
+            .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+            .map(|coord| coord.map(|(x, y)| self.cells[y * self.width + x]))
+            .collect())
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<C: Copy + Default> Write<C> for Grid<C> {
+    fn write_cell(&mut self, x: usize, y: usize, cell: C) -> Result<(), Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        self.cells[y * self.width + x] = cell;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,648 @@
+//! # HashLife
+//! An implementation of Bill Gosper's HashLife algorithm. The board is an unbounded quadtree of
+//! macrocells, where identical macrocells are only ever stored once and the future of each one is
+//! memoized. Patterns with a lot of repetition in space and time can then be advanced by huge
+//! numbers of generations at once.
+//!
+//! A macrocell at level `n` is a square of `2^n` by `2^n` cells. Level 0 macrocells are single
+//! cells, and every other macrocell is made of four children one level below it.
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::{error::Error, game_of_life::GameOfLife, read::Read, rule::Rule, sparse::SparseLife};
+
+pub(crate) type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+/// The smallest level the root is kept at.
+const MIN_LEVEL: u8 = 3;
+/// The largest level the root can grow to while coordinates still fit in an i64.
+const MAX_LEVEL: u8 = 62;
+/// An estimate of the memory used by each macrocell, including its entries in the lookup tables.
+const NODE_COST: usize = size_of::<Node>()
+    + size_of::<([NodeId; 4], NodeId)>()
+    + size_of::<((NodeId, u8), NodeId)>()
+    + 2 * size_of::<u64>();
+const DEFAULT_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    /// The north west, north east, south west and south east children.
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// The canonical store of macrocells. Every distinct macrocell is stored exactly once, so two
+/// macrocells are identical if and only if their ids are.
+#[derive(Debug, Clone)]
+struct Arena {
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    empty: Vec<NodeId>,
+}
+
+impl Arena {
+    fn new() -> Self {
+        let dead = Node {
+            level: 0,
+            children: [DEAD; 4],
+            population: 0,
+        };
+        let alive = Node {
+            population: 1,
+            ..dead
+        };
+        Arena {
+            nodes: vec![dead, alive],
+            index: HashMap::new(),
+            empty: vec![DEAD],
+        }
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id as usize]
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.node(id).level
+    }
+
+    fn population(&self, id: NodeId) -> u64 {
+        self.node(id).population
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.node(id).children
+    }
+
+    /// Returns the canonical macrocell made of the four provided children.
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(id) = self.index.get(&children) {
+            return *id;
+        }
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            level: self.level(nw) + 1,
+            children,
+            population: children.iter().map(|c| self.population(*c)).sum(),
+        });
+        self.index.insert(children, id);
+        id
+    }
+
+    /// Returns the macrocell at the provided level with every cell dead.
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().expect("Level 0 is always present");
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// The macrocell one level down, centered on the provided one.
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+        let (nw, ne, sw, se) = (
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        );
+        self.join(nw, ne, sw, se)
+    }
+
+    /// Copies a macrocell, and everything below it, from another arena into this one.
+    fn copy_from(
+        &mut self,
+        other: &Arena,
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(new_id) = copied.get(&id) {
+            return *new_id;
+        }
+        let [nw, ne, sw, se] = other
+            .children(id)
+            .map(|child| self.copy_from(other, child, copied));
+        let new_id = self.join(nw, ne, sw, se);
+        copied.insert(id, new_id);
+        new_id
+    }
+}
+
+/// An unbounded Game of Life universe run with the HashLife algorithm.
+///
+/// Cells are addressed with signed coordinates, and the universe grows as needed to fit the
+/// pattern. Rules where cells are born with zero neighbors (B0) aren't supported, since they would
+/// fill the infinite empty space around the pattern.
+#[derive(Debug, Clone)]
+pub struct HashLife {
+    arena: Arena,
+    results: HashMap<(NodeId, u8), NodeId>,
+    root: NodeId,
+    generation: u128,
+    rule: Rule,
+    table: [bool; 512],
+    memory_limit: usize,
+}
+
+impl HashLife {
+    /// Creates an empty universe running Conway's Game of Life.
+    pub fn new() -> Self {
+        let mut arena = Arena::new();
+        let root = arena.empty(MIN_LEVEL);
+        HashLife {
+            arena,
+            results: HashMap::new(),
+            root,
+            generation: 0,
+            rule: Rule::default(),
+            table: rule_table(Rule::default()),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        }
+    }
+
+    /// Sets the ruleset used to advance the universe. Fails if the rule has cells born with zero
+    /// neighbors, or its neighborhood reaches further than the adjacent cells.
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self, Error> {
+        if rule.is_birth(0) {
+            return Err(Error::Unsupported("HashLife rules with B0"));
+        }
+        if rule.neighborhood().radius() > 1 {
+            return Err(Error::Unsupported(
+                "HashLife rules with a neighborhood radius over 1",
+            ));
+        }
+        self.rule = rule;
+        self.table = rule_table(rule);
+        self.results.clear();
+        Ok(self)
+    }
+
+    /// The ruleset used to advance the universe.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Sets roughly how many bytes the macrocell cache may use. When the cache grows past this
+    /// limit, the next step first discards every macrocell that isn't part of the current pattern
+    /// along with all memoized results. Defaults to 256MiB.
+    pub fn with_memory_limit(self, bytes: usize) -> Self {
+        Self {
+            memory_limit: bytes,
+            ..self
+        }
+    }
+
+    /// The number of generations the universe has been advanced by.
+    pub fn generation(&self) -> u128 {
+        self.generation
+    }
+
+    /// The number of live cells in the universe.
+    pub fn population(&self) -> u64 {
+        self.arena.population(self.root)
+    }
+
+    /// The level of the root macrocell, which spans `2^level` cells in each direction centered on
+    /// the origin.
+    pub fn level(&self) -> u8 {
+        self.arena.level(self.root)
+    }
+
+    /// The number of macrocells currently cached.
+    pub fn node_count(&self) -> usize {
+        self.arena.nodes.len()
+    }
+
+    /// Returns the cell at the coordinates provided.
+    pub fn get_cell(&self, x: i64, y: i64) -> bool {
+        let half = self.half_size();
+        if !(-half..half).contains(&x) || !(-half..half).contains(&y) {
+            return false;
+        }
+        let (mut x, mut y) = ((x + half) as u64, (y + half) as u64);
+        let mut id = self.root;
+        for level in (0..self.level()).rev() {
+            let size = 1 << level;
+            let quadrant = usize::from(x >= size) + 2 * usize::from(y >= size);
+            id = self.arena.children(id)[quadrant];
+            x %= size;
+            y %= size;
+        }
+        id == ALIVE
+    }
+
+    /// Sets the cell at the coordinates provided, growing the universe if needed.
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) -> Result<(), Error> {
+        while !(-self.half_size()..self.half_size()).contains(&x)
+            || !(-self.half_size()..self.half_size()).contains(&y)
+        {
+            self.expand()?;
+        }
+        let half = self.half_size();
+        let level = self.level();
+        self.root = self.set_in(
+            self.root,
+            level,
+            (x + half) as u64,
+            (y + half) as u64,
+            alive,
+        );
+        Ok(())
+    }
+
+    fn set_in(&mut self, id: NodeId, level: u8, x: u64, y: u64, alive: bool) -> NodeId {
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let size = 1 << (level - 1);
+        let quadrant = usize::from(x >= size) + 2 * usize::from(y >= size);
+        let mut children = self.arena.children(id);
+        children[quadrant] = self.set_in(children[quadrant], level - 1, x % size, y % size, alive);
+        let [nw, ne, sw, se] = children;
+        self.arena.join(nw, ne, sw, se)
+    }
+
+    /// Advances the universe by `2^log2_generations` generations in a single step. Steps of more
+    /// than `2^59` generations are unsupported, since they need patterns wider than `2^62` cells.
+    pub fn step(&mut self, log2_generations: u8) -> Result<(), Error> {
+        if log2_generations > MAX_LEVEL - 3 {
+            return Err(Error::Unsupported("Steps of more than 2^59 generations"));
+        }
+        if self.arena.nodes.len().saturating_mul(NODE_COST) > self.memory_limit {
+            self.collect_garbage();
+        }
+        while self.level() < log2_generations + 3 || !self.is_padded() {
+            self.expand()?;
+        }
+        self.root = self.advance(self.root, log2_generations);
+        self.generation += 1 << log2_generations;
+        self.shrink();
+        Ok(())
+    }
+
+    /// Advances the universe by any number of generations, using the fewest possible steps.
+    pub fn advance_by(&mut self, generations: u64) -> Result<(), Error> {
+        for bit in 0..u64::BITS as u8 {
+            if generations & (1 << bit) != 0 {
+                self.step(bit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Discards every cached macrocell that isn't part of the current pattern, along with all
+    /// memoized results.
+    pub fn collect_garbage(&mut self) {
+        let mut arena = Arena::new();
+        self.root = arena.copy_from(&self.arena, self.root, &mut HashMap::new());
+        self.arena = arena;
+        self.results.clear();
+    }
+
+    /// The smallest rectangle containing every live cell, as `(min_x, min_y, max_x, max_y)`
+    /// inclusive. Returns None if the universe is empty.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        if self.population() == 0 {
+            return None;
+        }
+        let mut bounds = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
+        let half = self.half_size();
+        self.visit_bounds(self.root, -half, -half, &mut bounds);
+        Some(bounds)
+    }
+
+    fn visit_bounds(&self, id: NodeId, x: i64, y: i64, bounds: &mut (i64, i64, i64, i64)) {
+        let node = self.arena.node(id);
+        if node.population == 0 {
+            return;
+        }
+        let size = 1i64 << node.level;
+        // Skip subtrees that can't extend the bounds found so far.
+        if x >= bounds.0 && y >= bounds.1 && x + size - 1 <= bounds.2 && y + size - 1 <= bounds.3 {
+            return;
+        }
+        if node.level == 0 {
+            *bounds = (
+                bounds.0.min(x),
+                bounds.1.min(y),
+                bounds.2.max(x),
+                bounds.3.max(y),
+            );
+            return;
+        }
+        let half = size / 2;
+        for (i, child) in node.children.iter().enumerate() {
+            let (dx, dy) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+            self.visit_bounds(*child, x + dx, y + dy, bounds);
+        }
+    }
+
+    /// Copies a rectangle of the universe, with its top left corner at `(x, y)`, into a
+    /// [GameOfLife].
+    pub fn window(&self, x: i64, y: i64, width: usize, height: usize) -> GameOfLife {
+        let mut cells = vec![vec![false; width]; height];
+        let half = self.half_size();
+        let window = (x, y, x + width as i64, y + height as i64);
+        self.fill_window(self.root, -half, -half, window, &mut cells);
+        GameOfLife::from(cells).with_rule(self.rule)
+    }
+
+    fn fill_window(
+        &self,
+        id: NodeId,
+        x: i64,
+        y: i64,
+        window: (i64, i64, i64, i64),
+        cells: &mut [Vec<bool>],
+    ) {
+        let node = self.arena.node(id);
+        let size = 1i64 << node.level;
+        let overlaps = x < window.2 && x + size > window.0 && y < window.3 && y + size > window.1;
+        if node.population == 0 || !overlaps {
+            return;
+        }
+        if node.level == 0 {
+            cells[(y - window.1) as usize][(x - window.0) as usize] = true;
+            return;
+        }
+        let half = size / 2;
+        for (i, child) in node.children.iter().enumerate() {
+            let (dx, dy) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+            self.fill_window(*child, x + dx, y + dy, window, cells);
+        }
+    }
+
+    /// The root macrocell, for the .mc file format.
+    pub(crate) fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// Replaces the whole universe with the provided macrocell, for the .mc file format.
+    pub(crate) fn set_root(&mut self, root: NodeId, generation: u128) -> Result<(), Error> {
+        if self.arena.level(root) > MAX_LEVEL {
+            return Err(Error::Unsupported("Patterns wider than 2^62 cells"));
+        }
+        self.root = root;
+        self.generation = generation;
+        while self.level() < MIN_LEVEL {
+            self.expand()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn node_level(&self, id: NodeId) -> u8 {
+        self.arena.level(id)
+    }
+
+    pub(crate) fn node_population(&self, id: NodeId) -> u64 {
+        self.arena.population(id)
+    }
+
+    pub(crate) fn node_children(&self, id: NodeId) -> [NodeId; 4] {
+        self.arena.children(id)
+    }
+
+    /// Returns the canonical macrocell made of the north west, north east, south west and south
+    /// east children provided.
+    pub(crate) fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        let [nw, ne, sw, se] = children;
+        self.arena.join(nw, ne, sw, se)
+    }
+
+    pub(crate) fn empty_node(&mut self, level: u8) -> NodeId {
+        self.arena.empty(level)
+    }
+
+    pub(crate) fn cell_node(alive: bool) -> NodeId {
+        if alive {
+            ALIVE
+        } else {
+            DEAD
+        }
+    }
+
+    /// Calls the provided function with the coordinates of every live cell in the macrocell.
+    fn visit_cells(&self, id: NodeId, x: i64, y: i64, f: &mut impl FnMut(i64, i64)) {
+        let node = self.arena.node(id);
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            f(x, y);
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        for (i, child) in node.children.iter().enumerate() {
+            let (dx, dy) = ((i as i64 % 2) * half, (i as i64 / 2) * half);
+            self.visit_cells(*child, x + dx, y + dy, f);
+        }
+    }
+
+    fn half_size(&self) -> i64 {
+        1 << (self.level() - 1)
+    }
+
+    /// Doubles the size of the universe, keeping the pattern centered on the origin.
+    fn expand(&mut self) -> Result<(), Error> {
+        let level = self.level();
+        if level >= MAX_LEVEL {
+            return Err(Error::Unsupported("Patterns wider than 2^62 cells"));
+        }
+        let e = self.arena.empty(level - 1);
+        let [nw, ne, sw, se] = self.arena.children(self.root);
+        let nw = self.arena.join(e, e, e, nw);
+        let ne = self.arena.join(e, e, ne, e);
+        let sw = self.arena.join(e, sw, e, e);
+        let se = self.arena.join(se, e, e, e);
+        self.root = self.arena.join(nw, ne, sw, se);
+        Ok(())
+    }
+
+    /// Shrinks the universe while the whole pattern fits in the center of the root.
+    fn shrink(&mut self) {
+        while self.level() > MIN_LEVEL {
+            let center = self.arena.center(self.root);
+            if self.arena.population(center) != self.population() {
+                break;
+            }
+            self.root = center;
+        }
+    }
+
+    /// Whether every live cell lies in the central quarter of the central quarter of the root, so
+    /// that nothing can escape the root while it is advanced.
+    fn is_padded(&mut self) -> bool {
+        let center = self.arena.center(self.root);
+        let inner = self.arena.center(center);
+        self.arena.population(inner) == self.population()
+    }
+
+    /// Computes the center of a macrocell at level `n`, `2^k` generations into the future, where
+    /// `k <= n - 2`. The result is a macrocell at level `n - 1`.
+    fn advance(&mut self, id: NodeId, k: u8) -> NodeId {
+        let level = self.arena.level(id);
+        if self.arena.population(id) == 0 {
+            return self.arena.empty(level - 1);
+        }
+        if let Some(result) = self.results.get(&(id, k)) {
+            return *result;
+        }
+        let result = if level == 2 {
+            self.advance_base(id)
+        } else {
+            let fast = k == level - 2;
+            let [nw, ne, sw, se] = self.arena.children(id);
+            let [_, nw_ne, nw_sw, nw_se] = self.arena.children(nw);
+            let [ne_nw, _, ne_sw, ne_se] = self.arena.children(ne);
+            let [sw_nw, sw_ne, _, sw_se] = self.arena.children(sw);
+            let [se_nw, se_ne, se_sw, _] = self.arena.children(se);
+            let subnodes = [
+                nw,
+                self.arena.join(nw_ne, ne_nw, nw_se, ne_sw),
+                ne,
+                self.arena.join(nw_sw, nw_se, sw_nw, sw_ne),
+                self.arena.join(nw_se, ne_sw, sw_ne, se_nw),
+                self.arena.join(ne_sw, ne_se, se_nw, se_ne),
+                sw,
+                self.arena.join(sw_ne, se_nw, sw_se, se_sw),
+                se,
+            ];
+            let mut r = [DEAD; 9];
+            for (i, subnode) in subnodes.into_iter().enumerate() {
+                r[i] = if fast {
+                    self.advance(subnode, level - 3)
+                } else {
+                    self.arena.center(subnode)
+                };
+            }
+            let step = if fast { level - 3 } else { k };
+            let quadrants = [
+                self.arena.join(r[0], r[1], r[3], r[4]),
+                self.arena.join(r[1], r[2], r[4], r[5]),
+                self.arena.join(r[3], r[4], r[6], r[7]),
+                self.arena.join(r[4], r[5], r[7], r[8]),
+            ];
+            let [nw, ne, sw, se] = quadrants.map(|q| self.advance(q, step));
+            self.arena.join(nw, ne, sw, se)
+        };
+        self.results.insert((id, k), result);
+        result
+    }
+
+    /// Advances the center of a 4x4 macrocell by a single generation.
+    fn advance_base(&mut self, id: NodeId) -> NodeId {
+        let mut cells = 0u16;
+        for (i, child) in self.arena.children(id).into_iter().enumerate() {
+            for (j, cell) in self.arena.children(child).into_iter().enumerate() {
+                let x = (i % 2) * 2 + j % 2;
+                let y = (i / 2) * 2 + j / 2;
+                if cell == ALIVE {
+                    cells |= 1 << (y * 4 + x);
+                }
+            }
+        }
+        let next = [(1, 1), (2, 1), (1, 2), (2, 2)].map(|(x, y)| {
+            let mut index = 0;
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if cells & (1 << ((y + dy - 1) * 4 + x + dx - 1)) != 0 {
+                        index |= 1 << (dy * 3 + dx);
+                    }
+                }
+            }
+            if self.table[index] {
+                ALIVE
+            } else {
+                DEAD
+            }
+        });
+        self.arena.join(next[0], next[1], next[2], next[3])
+    }
+}
+
+impl Default for HashLife {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Precomputes the next state of a cell for every possible 3x3 neighborhood. Bit `dy * 3 + dx` of
+/// the index is the cell at offset `(dx - 1, dy - 1)`, so bit 4 is the cell itself. Only the cells
+/// in the rule's neighborhood are counted.
+fn rule_table(rule: Rule) -> [bool; 512] {
+    let neighborhood = rule.neighborhood();
+    let counted = (0..9)
+        .filter(|bit| neighborhood.contains(bit % 3 - 1, bit / 3 - 1))
+        .fold(0, |mask, bit| mask | 1 << bit);
+    let mut table = [false; 512];
+    for (index, next) in table.iter_mut().enumerate() {
+        let alive = index & (1 << 4) != 0;
+        let neighbors = (index & counted).count_ones() as usize;
+        *next = rule.next_state(alive, neighbors);
+    }
+    table
+}
+
+impl TryFrom<&GameOfLife> for HashLife {
+    type Error = Error;
+
+    /// Places the board in the universe with its top left corner at the origin.
+    fn try_from(value: &GameOfLife) -> Result<Self, Self::Error> {
+        let mut universe = HashLife::new().with_rule(value.rule())?;
+        let width = value.width().max(1);
+        for (i, alive) in value.iter().enumerate() {
+            if alive {
+                universe.set_cell((i % width) as i64, (i / width) as i64, true)?;
+            }
+        }
+        Ok(universe)
+    }
+}
+
+impl From<&HashLife> for GameOfLife {
+    /// Copies the universe into a board, cropped to the bounding box of the pattern.
+    fn from(value: &HashLife) -> Self {
+        match value.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => value.window(
+                min_x,
+                min_y,
+                (max_x - min_x + 1) as usize,
+                (max_y - min_y + 1) as usize,
+            ),
+            None => GameOfLife::from(Vec::new()).with_rule(value.rule),
+        }
+    }
+}
+
+impl TryFrom<&SparseLife> for HashLife {
+    type Error = Error;
+
+    fn try_from(value: &SparseLife) -> Result<Self, Self::Error> {
+        let mut universe = HashLife::new().with_rule(value.rule())?;
+        for (x, y) in value.iter() {
+            universe.set_cell(x, y, true)?;
+        }
+        Ok(universe)
+    }
+}
+
+impl From<&HashLife> for SparseLife {
+    fn from(value: &HashLife) -> Self {
+        let mut board = SparseLife::new()
+            .with_rule(value.rule)
+            .expect("HashLife rules never have B0");
+        let half = value.half_size();
+        value.visit_cells(value.root, -half, -half, &mut |x, y| board.set(x, y, true));
+        board
+    }
+}
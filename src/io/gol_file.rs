@@ -1,13 +1,27 @@
-use std::io::{self, Read as IORead, Seek, SeekFrom};
+use std::collections::BTreeMap;
+use std::io::{self, Read as IORead, Seek, SeekFrom, Write as IOWrite};
 
+use super::{Header, Metadata};
+use crate::error::Error;
+use crate::neighborhood::Neighborhood;
 use crate::read::Read as GoLRead;
+use crate::topology::Topology;
 use crate::utils::neighbor_coordinates;
+use crate::write::Write as GoLWrite;
 
 const DEFAULT_BUF_SIZE: usize = 3000;
 
 /// A memory-efficient way to read and interact with a Game Of Life file (.gol) currently saved to
 /// disk. Similar to buffered writer, the struct will efficiently use a buffer to try to limit the
 /// number of reads made to disk while avoiding bringing the entire file into memory.
+///
+/// Every version of the .gol format is supported, so bit-packed version 2 files can be read just
+/// as randomly as version 1 files.
+///
+/// When the underlying file is also writable, cells can be changed in place through
+/// [GoLWrite]. Writes are held in memory, alongside the read buffer, until there are as many as
+/// the buffer has cells or [GoLWrite::flush] is called. Writes that haven't been flushed are lost
+/// when the GoLFile is dropped.
 #[derive(Debug)]
 pub struct GoLFile<R> {
     inner: R,
@@ -15,35 +29,35 @@ pub struct GoLFile<R> {
     buffer: Vec<Vec<Option<bool>>>,
     pub width: usize,
     pub height: usize,
+    header: Header,
+    metadata: Metadata,
+    topology: Topology,
     file_read_count: usize,
-    _temp: Vec<u8>,
+    row_bytes: Vec<u8>,
+    row_cells: Vec<bool>,
+    /// Writes that haven't reached the file yet, keyed by `(y, x)` so each row is contiguous.
+    pending: BTreeMap<(usize, usize), bool>,
 }
 impl<R: IORead + Seek> GoLFile<R> {
-    const HEADER_SIZE: usize = 12;
-
     /// Creates a new GoLFile from a reader. Defaults to a maximum buffer size of 3KB. Actual size
     /// depends on the width/height of the board.
-    pub fn new(inner: R) -> Result<Self, &'static str> {
+    pub fn new(inner: R) -> Result<Self, Error> {
         Self::with_max_capacity(inner, DEFAULT_BUF_SIZE)
     }
 
     /// Creates a new GoLFile from a reader, capping the buffer size to the provided number of
     /// bytes. Actual size of the buffer will depend on the width/height of the board.
-    pub fn with_max_capacity(mut inner: R, max_capacity: usize) -> Result<Self, &'static str> {
-        let mut header = [0; 12];
-        inner
-            .read_exact(&mut header)
-            .or(Err("Failed to read header"))?;
-        if !header.starts_with(b"GOFL") {
-            return Err("File isn't a Game of Life file");
-        }
-        let width = u32::from_be_bytes(header[4..8].try_into().expect("File should be long enough"))
-            as usize;
-        let height = u32::from_be_bytes(
-            header[8..12]
-                .try_into()
-                .expect("File should be long enough"),
-        ) as usize;
+    pub fn with_max_capacity(mut inner: R, max_capacity: usize) -> Result<Self, Error> {
+        let header = Header::read(&mut inner)?;
+        let metadata = header.read_metadata(&mut inner)?;
+        let expected = header.body_len()?;
+        let got = inner
+            .seek(SeekFrom::End(0))?
+            .saturating_sub(header.data_offset()) as usize;
+        if got < expected {
+            return Err(Error::TruncatedBody { expected, got });
+        }
+        let (width, height) = (header.width, header.height);
         let (b_width, b_height) = if max_capacity / width >= 3 {
             (width, max_capacity / width)
         } else {
@@ -60,11 +74,43 @@ impl<R: IORead + Seek> GoLFile<R> {
             buffer,
             width,
             height,
+            header,
+            metadata,
+            topology: Topology::default(),
             file_read_count: 0,
-            _temp: vec![2; b_width],
+            row_bytes: vec![0; b_width + 1],
+            row_cells: vec![false; b_width],
+            pending: BTreeMap::new(),
         })
     }
 
+    /// The header of the underlying file, describing its version and layout.
+    pub fn header(&self) -> Header {
+        self.header
+    }
+
+    /// The metadata stored in the underlying file.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Unwraps the GoLFile, returning the underlying reader. Writes that haven't been flushed are
+    /// lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
     fn load_buffer(&mut self, mut x: usize, mut y: usize) -> io::Result<()> {
         x = usize::min(x, self.width.saturating_sub(self.buffer[0].len()));
         y = usize::min(y, self.height.saturating_sub(self.buffer.len()));
@@ -82,39 +128,43 @@ impl<R: IORead + Seek> GoLFile<R> {
     }
 
     fn read_row(&mut self, x: usize, y: usize, buf_index: usize) -> io::Result<()> {
-        let buffer_write_range = {
-            let end = usize::min(self.width - x, self.buffer[0].len());
-            0..end
-        };
-        self.inner
-            .seek(SeekFrom::Start(self.compute_index(x, y) as u64))?;
-        self._temp.fill(2);
+        let len = usize::min(self.width - x, self.buffer[0].len());
+        let bytes = self.header.span_bytes(x, len);
         self.inner
-            .read_exact(&mut self._temp[buffer_write_range.clone()])?;
+            .seek(SeekFrom::Start(self.header.cell_offset(x, y)))?;
+        self.inner.read_exact(&mut self.row_bytes[..bytes])?;
+        self.header
+            .decode_cells(x, &self.row_bytes[..bytes], &mut self.row_cells[..len]);
         let buffer = &mut self.buffer[buf_index];
-        for i in buffer_write_range {
-            buffer[i] = if self._temp[i] == 2 {
-                None
-            } else {
-                Some(self._temp[i] == 1)
-            }
+        for (cell, value) in buffer.iter_mut().zip(&self.row_cells[..len]) {
+            *cell = Some(*value);
+        }
+        for ((_, p_x), alive) in self.pending.range((y, x)..(y, x + len)) {
+            buffer[p_x - x] = Some(*alive);
         }
         self.file_read_count += 1;
         Ok(())
     }
 
-    fn compute_index(&self, x: usize, y: usize) -> usize {
-        y * self.width + x + Self::HEADER_SIZE
+    /// Reads a single cell straight from the file, bypassing the buffer. Used for neighbors that
+    /// wrap around to the other side of the board and so are unlikely to be buffered.
+    fn read_cell_unbuffered(&mut self, x: usize, y: usize) -> io::Result<bool> {
+        if let Some(alive) = self.pending.get(&(y, x)) {
+            return Ok(*alive);
+        }
+        let mut byte = [0];
+        let mut cell = [false];
+        self.inner
+            .seek(SeekFrom::Start(self.header.cell_offset(x, y)))?;
+        self.inner.read_exact(&mut byte)?;
+        self.header.decode_cells(x, &byte, &mut cell);
+        self.file_read_count += 1;
+        Ok(cell[0])
     }
 
-    fn get_buffer_coordinates(
-        &mut self,
-        x: usize,
-        y: usize,
-    ) -> Result<(usize, usize), &'static str> {
+    fn get_buffer_coordinates(&mut self, x: usize, y: usize) -> Result<(usize, usize), Error> {
         if !self.buffer_contains(x, y) {
-            self.load_buffer(x.saturating_sub(1), y.saturating_sub(1))
-                .or(Err("Failed to read file contents"))?;
+            self.load_buffer(x.saturating_sub(1), y.saturating_sub(1))?;
         }
         let (c_x, c_y) = self.cursor.expect("Cursor should have been set");
         Ok((x - c_x, y - c_y))
@@ -143,6 +193,63 @@ impl<R: IORead + Seek> GoLFile<R> {
     }
 }
 
+impl<R: IORead + IOWrite + Seek> GoLFile<R> {
+    /// Writes the pending cells to the file. Each row is read, patched and written back as a
+    /// single span, which keeps the other cells sharing a byte intact in bit-packed files.
+    fn write_pending(&mut self) -> io::Result<()> {
+        let pending: Vec<_> = self.pending.iter().map(|(&(y, x), &a)| (y, x, a)).collect();
+        let mut bytes = Vec::new();
+        let mut cells = Vec::new();
+        for run in pending.chunk_by(|a, b| a.0 == b.0) {
+            let (y, start, _) = run[0];
+            let len = run[run.len() - 1].1 - start + 1;
+            bytes.resize(self.header.span_bytes(start, len), 0);
+            cells.resize(len, false);
+            let offset = SeekFrom::Start(self.header.cell_offset(start, y));
+            self.inner.seek(offset)?;
+            self.inner.read_exact(&mut bytes)?;
+            self.header.decode_cells(start, &bytes, &mut cells);
+            for (_, x, alive) in run {
+                cells[x - start] = *alive;
+            }
+            self.header.encode_cells(start, &cells, &mut bytes);
+            self.inner.seek(offset)?;
+            self.inner.write_all(&bytes)?;
+        }
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl<R: IORead + IOWrite + Seek> GoLWrite for GoLFile<R> {
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        // Keep the read buffer in step, so the write is visible without reloading it.
+        if let Some((c_x, c_y)) = self.cursor {
+            if let Some(Some(cell)) = y
+                .checked_sub(c_y)
+                .zip(x.checked_sub(c_x))
+                .and_then(|(b_y, b_x)| self.buffer.get_mut(b_y)?.get_mut(b_x))
+            {
+                *cell = alive;
+            }
+        }
+        self.pending.insert((y, x), alive);
+        if self.pending.len() >= self.buffer.len() * self.buffer[0].len() {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.write_pending()?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
 impl<R: IORead + Seek> IntoIterator for GoLFile<R> {
     type IntoIter = IntoIter<R>;
     type Item = bool;
@@ -157,18 +264,22 @@ impl<R: IORead + Seek> IntoIterator for GoLFile<R> {
 }
 
 impl<R: IORead + Seek> GoLRead for GoLFile<R> {
-    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
         let (b_x, b_y) = self.get_buffer_coordinates(x, y)?;
         self.buffer
             .get(b_y)
             .and_then(|column| column.get(b_x))
-            .ok_or("Cell was not in range")?
-            .ok_or("Cell shouldn't be empty")
+            .copied()
+            .flatten()
+            .ok_or(Error::OutOfBounds { x, y })
     }
 
-    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], &'static str> {
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
         let (b_x, b_y) = self.get_buffer_coordinates(x, y)?;
-        let neighbors = neighbor_coordinates(b_x, b_y).map(|b_coord| {
+        let mut neighbors = neighbor_coordinates(b_x, b_y).map(|b_coord| {
             b_coord.and_then(|coord| {
                 self.buffer
                     .get(coord.1)
@@ -177,9 +288,40 @@ impl<R: IORead + Seek> GoLRead for GoLFile<R> {
                     .unwrap_or_default()
             })
         });
+        if self.topology != Topology::Bounded {
+            let on_board = neighbor_coordinates(x, y)
+                .map(|coord| coord.filter(|(x, y)| *x < self.width && *y < self.height));
+            let wrapped = self
+                .topology
+                .neighbor_coordinates(x, y, self.width, self.height);
+            for i in 0..neighbors.len() {
+                if let (None, Some((w_x, w_y))) = (on_board[i], wrapped[i]) {
+                    neighbors[i] = Some(self.read_cell_unbuffered(w_x, w_y)?);
+                }
+            }
+        }
         Ok(neighbors)
     }
 
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        let coordinates: Vec<_> = self
+            .topology
+            .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+            .collect();
+        coordinates
+            .into_iter()
+            .map(|coord| coord.map(|(x, y)| self.read_cell(x, y)).transpose())
+            .collect()
+    }
+
     fn width(&self) -> usize {
         self.width
     }
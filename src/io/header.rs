@@ -0,0 +1,329 @@
+use std::io;
+
+use super::Metadata;
+use crate::error::{header_error, Error};
+
+/// The magic bytes that start a version 1 .gol file.
+const MAGIC_V1: &[u8; 4] = b"GOFL";
+/// The magic bytes that start a versioned (version 2 and later) .gol file.
+const MAGIC_VERSIONED: &[u8; 4] = b"GOFV";
+const V1_SIZE: usize = 12;
+const V2_SIZE: usize = 16;
+
+/// Set when every cell is stored as a single bit.
+const FLAG_PACKED: u8 = 1;
+/// Set when cells hold one of more than two states, stored a byte per cell.
+const FLAG_STATES: u8 = 4;
+/// Set when the extension holds a metadata block.
+const FLAG_METADATA: u8 = 0x10;
+/// Flags that change how the cells are stored, which readers must understand. The other flags
+/// describe the extension, which readers can skip without knowing what is in it.
+const REQUIRED_FLAGS: u8 = 0x0f;
+const KNOWN_FLAGS: u8 = FLAG_PACKED | FLAG_STATES | FLAG_METADATA;
+/// The extension between the header and the cells is sized in units of this many bytes.
+const EXTENSION_UNIT: usize = 256;
+
+/// The versions of the .gol file format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// The original format, one byte per cell.
+    #[default]
+    V1,
+    /// The versioned format, with a flags field and bit-packed rows.
+    V2,
+}
+
+/// The layout of a .gol file, as described by its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub version: Version,
+    pub width: usize,
+    pub height: usize,
+    flags: u8,
+    /// The length of the extension, in units of [EXTENSION_UNIT].
+    extension: u8,
+    states: u16,
+}
+
+impl Header {
+    /// Creates the header for a board of the provided size. Version 2 files are always
+    /// bit-packed.
+    pub fn new(version: Version, width: usize, height: usize) -> Self {
+        let flags = match version {
+            Version::V1 => 0,
+            Version::V2 => FLAG_PACKED,
+        };
+        Header {
+            version,
+            width,
+            height,
+            flags,
+            extension: 0,
+            states: 2,
+        }
+    }
+
+    /// Marks that cells hold one of `states` states, stored a byte per cell. Only version 2 files
+    /// can hold more than two states, and at most 256.
+    pub(crate) fn with_states(self, states: u16) -> Result<Self, Error> {
+        match states {
+            2 => Ok(self),
+            3..=256 if self.version == Version::V2 => Ok(Header {
+                flags: (self.flags | FLAG_STATES) & !FLAG_PACKED,
+                states,
+                ..self
+            }),
+            3..=256 => Err(Error::Unsupported(
+                "More than two states in version 1 files",
+            )),
+            _ => Err(Error::Unsupported("Boards with more than 256 states")),
+        }
+    }
+
+    /// Marks that a metadata block of the provided length follows the header, making room for it
+    /// and its length in the extension. Only version 2 files can carry metadata, and at most
+    /// 64KiB of it.
+    pub(crate) fn with_metadata_len(self, len: usize) -> Result<Self, Error> {
+        if self.version == Version::V1 {
+            return Err(Error::Unsupported("Metadata in version 1 files"));
+        }
+        let extension = u8::try_from((len + 4).div_ceil(EXTENSION_UNIT))
+            .or(Err(Error::Unsupported("Metadata blocks over 64KiB")))?;
+        Ok(Header {
+            flags: self.flags | FLAG_METADATA,
+            extension,
+            ..self
+        })
+    }
+
+    /// Reads the header from the start of a .gol file, leaving the reader at the metadata block if
+    /// there is one, or the first cell otherwise.
+    pub fn read(mut input: impl io::Read) -> Result<Self, Error> {
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic).map_err(header_error)?;
+        match &magic {
+            MAGIC_V1 => {
+                let mut dimensions = [0; 8];
+                input.read_exact(&mut dimensions).map_err(header_error)?;
+                let (width, height) = read_dimensions(&dimensions);
+                Ok(Header::new(Version::V1, width, height))
+            }
+            MAGIC_VERSIONED => {
+                let mut rest = [0; V2_SIZE - 4];
+                input.read_exact(&mut rest).map_err(header_error)?;
+                if rest[0] != 2 {
+                    return Err(Error::UnsupportedVersion(rest[0]));
+                }
+                // Unknown flags that only describe the extension can be ignored, since its length
+                // is known either way.
+                let flags = rest[1] & (REQUIRED_FLAGS | KNOWN_FLAGS);
+                if flags & REQUIRED_FLAGS & !KNOWN_FLAGS != 0 {
+                    return Err(Error::Unsupported(".gol flags"));
+                }
+                if flags & FLAG_STATES != 0 && flags & FLAG_PACKED != 0 {
+                    return Err(Error::Malformed("Multi-state cells can't be bit-packed"));
+                }
+                let states = if flags & FLAG_STATES != 0 {
+                    u16::from(rest[2]) + 1
+                } else {
+                    2
+                };
+                let (width, height) = read_dimensions(&rest[4..12]);
+                Ok(Header {
+                    version: Version::V2,
+                    width,
+                    height,
+                    flags,
+                    extension: rest[3],
+                    states,
+                })
+            }
+            _ => Err(Error::BadMagic),
+        }
+    }
+
+    /// The bytes of the header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(V2_SIZE);
+        match self.version {
+            Version::V1 => bytes.extend_from_slice(MAGIC_V1),
+            Version::V2 => {
+                bytes.extend_from_slice(MAGIC_VERSIONED);
+                let states = if self.flags & FLAG_STATES != 0 {
+                    (self.states - 1) as u8
+                } else {
+                    0
+                };
+                bytes.extend_from_slice(&[2, self.flags, states, self.extension]);
+            }
+        }
+        bytes.extend_from_slice(&(self.width as u32).to_be_bytes());
+        bytes.extend_from_slice(&(self.height as u32).to_be_bytes());
+        bytes
+    }
+
+    /// Whether a metadata block follows the header.
+    pub fn has_metadata(&self) -> bool {
+        self.flags & FLAG_METADATA != 0
+    }
+
+    /// The number of bytes between the header and the first cell.
+    pub fn extension_len(&self) -> usize {
+        usize::from(self.extension) * EXTENSION_UNIT
+    }
+
+    /// Reads the extension that follows the header, leaving the reader at the first cell, and
+    /// returns the metadata block in it. Returns empty metadata if the file has none.
+    pub fn read_metadata(&self, input: impl io::Read) -> Result<Metadata, Error> {
+        let mut bytes = Vec::new();
+        let expected = self.extension_len();
+        io::Read::read_to_end(&mut input.take(expected as u64), &mut bytes)?;
+        if bytes.len() < expected {
+            return Err(Error::Malformed("Header extension is truncated"));
+        }
+        if !self.has_metadata() {
+            return Ok(Metadata::default());
+        }
+        let (len, block) = bytes
+            .split_first_chunk::<4>()
+            .ok_or(Error::Malformed("Metadata block is truncated"))?;
+        let block = block
+            .get(..u32::from_be_bytes(*len) as usize)
+            .ok_or(Error::Malformed("Metadata block is truncated"))?;
+        Metadata::from_bytes(block)
+    }
+
+    /// The number of states each cell can be in. Two state files hold dead (0) and alive (1)
+    /// cells.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Whether each cell is stored as a single bit rather than a byte.
+    pub fn is_packed(&self) -> bool {
+        self.flags & FLAG_PACKED != 0
+    }
+
+    /// The position in the file of the first cell.
+    pub fn data_offset(&self) -> u64 {
+        match self.version {
+            Version::V1 => V1_SIZE as u64,
+            Version::V2 => (V2_SIZE + self.extension_len()) as u64,
+        }
+    }
+
+    /// The number of bytes used to store each row.
+    pub fn row_bytes(&self) -> usize {
+        if self.is_packed() {
+            self.width.div_ceil(8)
+        } else {
+            self.width
+        }
+    }
+
+    /// The number of bytes used to store every cell. Fails if that can't be addressed.
+    pub(crate) fn body_len(&self) -> Result<usize, Error> {
+        self.row_bytes()
+            .checked_mul(self.height)
+            .ok_or(Error::Unsupported("Boards too large to address"))
+    }
+
+    /// Reads every cell's bytes, leaving the reader after the last one. The buffer grows as bytes
+    /// arrive rather than trusting the size in the header.
+    pub(crate) fn read_body(&self, input: impl io::Read) -> Result<Vec<u8>, Error> {
+        let expected = self.body_len()?;
+        let mut body = Vec::new();
+        io::Read::read_to_end(&mut input.take(expected as u64), &mut body)?;
+        if body.len() < expected {
+            return Err(Error::TruncatedBody {
+                expected,
+                got: body.len(),
+            });
+        }
+        Ok(body)
+    }
+
+    /// The position in the file of the byte holding the provided cell.
+    pub fn cell_offset(&self, x: usize, y: usize) -> u64 {
+        let column = if self.is_packed() { x / 8 } else { x };
+        self.data_offset() + (y * self.row_bytes() + column) as u64
+    }
+
+    /// Decodes the cells of a row, or part of one starting at column `x`, from its bytes.
+    pub fn decode_cells(&self, x: usize, bytes: &[u8], cells: &mut [bool]) {
+        if self.is_packed() {
+            for (i, cell) in cells.iter_mut().enumerate() {
+                let bit = x % 8 + i;
+                *cell = bytes[bit / 8] & (0x80 >> (bit % 8)) != 0;
+            }
+        } else {
+            for (cell, byte) in cells.iter_mut().zip(bytes) {
+                *cell = *byte == 1;
+            }
+        }
+    }
+
+    /// Decodes the states of a row, or part of one starting at column `x`, from its bytes.
+    pub fn decode_states(&self, x: usize, bytes: &[u8], states: &mut [u8]) {
+        if self.is_packed() {
+            for (i, state) in states.iter_mut().enumerate() {
+                let bit = x % 8 + i;
+                *state = u8::from(bytes[bit / 8] & (0x80 >> (bit % 8)) != 0);
+            }
+        } else {
+            states.copy_from_slice(&bytes[..states.len()]);
+        }
+    }
+
+    /// Encodes the cells of a row, or part of one starting at column `x`, into bytes previously
+    /// read from the file. Bits in the bytes that lie outside the cells are left untouched.
+    pub fn encode_cells(&self, x: usize, cells: &[bool], bytes: &mut [u8]) {
+        if self.is_packed() {
+            for (i, alive) in cells.iter().enumerate() {
+                let bit = x % 8 + i;
+                let mask = 0x80 >> (bit % 8);
+                if *alive {
+                    bytes[bit / 8] |= mask;
+                } else {
+                    bytes[bit / 8] &= !mask;
+                }
+            }
+        } else {
+            for (byte, alive) in bytes.iter_mut().zip(cells) {
+                *byte = u8::from(*alive);
+            }
+        }
+    }
+
+    /// Encodes a full row of cells into bytes.
+    pub fn encode_row(&self, row: &[bool], bytes: &mut Vec<u8>) {
+        if self.is_packed() {
+            bytes.extend(row.chunks(8).map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, alive)| **alive)
+                    .fold(0, |byte, (i, _)| byte | (0x80 >> i))
+            }));
+        } else {
+            bytes.extend(row.iter().map(|alive| u8::from(*alive)));
+        }
+    }
+
+    /// The number of bytes needed to hold `len` cells starting at column `x`.
+    pub fn span_bytes(&self, x: usize, len: usize) -> usize {
+        if len == 0 {
+            0
+        } else if self.is_packed() {
+            (x + len - 1) / 8 - x / 8 + 1
+        } else {
+            len
+        }
+    }
+}
+
+fn read_dimensions(bytes: &[u8]) -> (usize, usize) {
+    let width = u32::from_be_bytes(bytes[0..4].try_into().expect("Slice is 4 bytes")) as usize;
+    let height = u32::from_be_bytes(bytes[4..8].try_into().expect("Slice is 4 bytes")) as usize;
+    (width, height)
+}
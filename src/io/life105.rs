@@ -0,0 +1,137 @@
+//! # Life 1.05 File Format
+//! Life 1.05 files start with a `#Life 1.05` line, followed by optional `#D` description lines
+//! and a rule line. `#N` means Conway's Game of Life, otherwise `#R` gives the rule in S/B
+//! notation.
+//!
+//! The cells are drawn in blocks, each starting with a `#P x y` line giving the position of the
+//! block's top left corner. Within a block `.` is a dead cell and `*` is a live cell, and dead
+//! cells at the end of a row may be omitted.
+//!
+//! Reading a file produces a board cropped to the live cells.
+
+use std::io::{self, Write};
+
+use super::Comments;
+use crate::{
+    error::Error, game_of_life::GameOfLife, neighborhood::Neighborhood, read::Read, rule::Rule,
+};
+
+pub(crate) const HEADER: &str = "#Life 1.05";
+
+/// Returned when a block runs past the largest coordinate.
+const OVERFLOW: Error = Error::Malformed("Life 1.05 block runs past the largest coordinate");
+
+/// Create a GameOfLife by reading Life 1.05 formatted data.
+pub fn read_life105(input: impl io::Read) -> Result<GameOfLife, Error> {
+    read_life105_with_comments(input).map(|(board, _)| board)
+}
+
+/// Create a GameOfLife by reading Life 1.05 formatted data, also returning the file's
+/// descriptions as comments.
+pub fn read_life105_with_comments(
+    mut input: impl io::Read,
+) -> Result<(GameOfLife, Comments), Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some(HEADER) {
+        return Err(Error::BadMagic);
+    }
+    let mut comments = Comments::default();
+    let mut rule = Rule::default();
+    let mut cells = Vec::new();
+    let mut block = None;
+    for line in lines {
+        if let Some(description) = line.strip_prefix("#D") {
+            comments.comments.push(description.trim().to_string());
+        } else if line.starts_with("#N") {
+            rule = Rule::CONWAY;
+        } else if let Some(rulestring) = line.strip_prefix("#R") {
+            rule = rulestring.trim().parse()?;
+        } else if let Some(position) = line.strip_prefix("#P") {
+            let mut coordinates = position.split_whitespace().map(str::parse::<i64>);
+            block = match (coordinates.next(), coordinates.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Some((x, y)),
+                _ => return Err(Error::Malformed("Malformed Life 1.05 block position")),
+            };
+        } else if !line.starts_with('#') {
+            let (x, y) = block
+                .as_mut()
+                .ok_or(Error::Malformed("Life 1.05 cells must be in a block"))?;
+            for (i, c) in line.chars().enumerate() {
+                match c {
+                    '.' => {}
+                    '*' => cells.push((x.checked_add(i as i64).ok_or(OVERFLOW)?, *y)),
+                    _ => return Err(Error::Malformed("Unexpected character in Life 1.05 data")),
+                }
+            }
+            *y = y.checked_add(1).ok_or(OVERFLOW)?;
+        }
+    }
+    Ok((super::crop_to_cells(&cells)?.with_rule(rule), comments))
+}
+
+/// Writes a Life 1.05 file to output.
+pub fn write_life105(output: impl io::Write, state: &GameOfLife) -> Result<(), Error> {
+    write_life105_with_comments(output, state, &Comments::default())
+}
+
+/// Writes a Life 1.05 file to output, with the comments as descriptions. The format has no place
+/// for a name or author, so they are written as descriptions too.
+pub fn write_life105_with_comments(
+    output: impl io::Write,
+    state: &GameOfLife,
+    comments: &Comments,
+) -> Result<(), Error> {
+    let mut output = io::BufWriter::new(output);
+    let mut contents = format!("{HEADER}\n");
+    let descriptions = comments
+        .name
+        .iter()
+        .chain(&comments.author)
+        .chain(&comments.comments);
+    for description in descriptions {
+        contents.push_str(&format!("#D {description}\n"));
+    }
+    if state.rule() == Rule::CONWAY {
+        contents.push_str("#N\n");
+    } else {
+        contents.push_str(&format!("#R {}\n", s_b_notation(state.rule())));
+    }
+    contents.push_str("#P 0 0\n");
+    let cells: Vec<bool> = state.iter().collect();
+    for row in cells.chunks(state.width().max(1)) {
+        let end = row.iter().rposition(|c| *c).map_or(0, |i| i + 1);
+        contents.extend(row[..end].iter().map(|c| if *c { '*' } else { '.' }));
+        if end == 0 {
+            contents.push('.');
+        }
+        contents.push('\n');
+    }
+    output
+        .write_all(contents.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(Error::Io)
+}
+
+/// Formats a rule in the S/B notation used by Life 1.05, such as `23/3`. Rules over larger
+/// neighborhoods don't fit the notation, and are written in HROT notation instead.
+fn s_b_notation(rule: Rule) -> String {
+    let suffix = match rule.neighborhood() {
+        Neighborhood::Moore(1) => "",
+        Neighborhood::VonNeumann(1) => "V",
+        Neighborhood::Hexagonal => "H",
+        _ => return rule.to_string(),
+    };
+    let counts = |f: fn(&Rule, usize) -> bool| -> String {
+        (0..=8)
+            .filter(|n| f(&rule, *n))
+            .map(|n| n.to_string())
+            .collect()
+    };
+    format!(
+        "{}/{}{suffix}",
+        counts(Rule::is_survival),
+        counts(Rule::is_birth)
+    )
+}
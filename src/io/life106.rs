@@ -0,0 +1,45 @@
+//! # Life 1.06 File Format
+//! Life 1.06 files start with a `#Life 1.06` line, followed by the coordinates of each live cell
+//! as a pair of signed integers on its own line.
+//!
+//! The format doesn't record the size of the board, so reading a file produces a board cropped to
+//! the live cells.
+
+use std::io::{self, Write};
+
+use crate::{error::Error, game_of_life::GameOfLife, read::Read};
+
+pub(crate) const HEADER: &str = "#Life 1.06";
+
+/// Create a GameOfLife by reading Life 1.06 formatted data.
+pub fn read_life106(mut input: impl io::Read) -> Result<GameOfLife, Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some(HEADER) {
+        return Err(Error::BadMagic);
+    }
+    let mut cells = Vec::new();
+    for line in lines.filter(|l| !l.starts_with('#')) {
+        let mut coordinates = line.split_whitespace().map(str::parse::<i64>);
+        match (coordinates.next(), coordinates.next(), coordinates.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => cells.push((x, y)),
+            _ => return Err(Error::Malformed("Malformed Life 1.06 coordinates")),
+        }
+    }
+    super::crop_to_cells(&cells)
+}
+
+/// Writes a Life 1.06 file to output, with the top left corner of the board at the origin.
+pub fn write_life106(output: impl io::Write, state: &GameOfLife) -> Result<(), Error> {
+    let mut output = io::BufWriter::new(output);
+    let width = state.width().max(1);
+    let mut contents = format!("{HEADER}\n");
+    for (i, _) in state.iter().enumerate().filter(|(_, alive)| *alive) {
+        contents.push_str(&format!("{} {}\n", i % width, i / width));
+    }
+    output
+        .write_all(contents.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(Error::Io)
+}
@@ -0,0 +1,212 @@
+//! # Macrocell File Format
+//! Macrocell (.mc) files store a [HashLife] quadtree directly, so huge patterns with a lot of
+//! repetition take up very little space.
+//!
+//! The file starts with an `[M2]` line, optionally followed by `#R` with the rule, `#G` with the
+//! generation count and `#C` comment lines. Every following line defines a macrocell, numbered
+//! from 1 in the order they appear:
+//! * A level 3 macrocell (8x8 cells) is drawn with `.` for dead cells, `*` for live cells and `$`
+//!   to end each row. Dead cells at the end of a row and empty rows at the end are omitted.
+//! * Any larger macrocell is written as `level nw ne sw se`, where the children are the numbers
+//!   of earlier macrocells, or 0 for an empty one.
+//!
+//! The last macrocell is the root of the pattern, centered on the origin.
+//!
+//! To store one of the fixed size board types, convert it to a [HashLife] first. The board is
+//! placed with its top left corner at the origin.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::error::Error;
+use crate::hashlife::{HashLife, NodeId};
+
+const HEADER: &str = "[M2]";
+const LEAF_LEVEL: u8 = 3;
+
+/// Create a HashLife universe by reading macrocell formatted data.
+pub fn read_mc(mut input: impl io::Read) -> Result<HashLife, Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    if !lines.next().is_some_and(|l| l.starts_with(HEADER)) {
+        return Err(Error::BadMagic);
+    }
+    let mut universe = HashLife::new();
+    let mut generation = 0;
+    // Node numbers start at 1, so 0 is never looked up.
+    let mut nodes: Vec<NodeId> = vec![0];
+    for line in lines {
+        if let Some(rulestring) = line.strip_prefix("#R") {
+            let rulestring = rulestring.trim();
+            let rulestring = rulestring.split_once(':').map_or(rulestring, |(r, _)| r);
+            universe = universe.with_rule(rulestring.parse()?)?;
+        } else if let Some(count) = line.strip_prefix("#G") {
+            generation = count
+                .trim()
+                .parse()
+                .or(Err(Error::Malformed("Invalid macrocell generation")))?;
+        } else if line.starts_with('#') {
+            continue;
+        } else if line.starts_with(|c: char| c.is_ascii_digit()) {
+            let node = read_node(&mut universe, &nodes, line)?;
+            nodes.push(node);
+        } else {
+            let leaf = read_leaf(&mut universe, line)?;
+            nodes.push(leaf);
+        }
+    }
+    match nodes.last() {
+        Some(root) if nodes.len() > 1 => universe.set_root(*root, generation)?,
+        _ => {}
+    }
+    Ok(universe)
+}
+
+fn read_node(universe: &mut HashLife, nodes: &[NodeId], line: &str) -> Result<NodeId, Error> {
+    let fields = line
+        .split_whitespace()
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<_>, _>>()
+        .or(Err(Error::Malformed("Malformed macrocell node")))?;
+    let [level, children @ ..] = fields.as_slice() else {
+        return Err(Error::Malformed("Malformed macrocell node"));
+    };
+    if children.len() != 4 || !(LEAF_LEVEL as usize + 1..=u8::MAX as usize).contains(level) {
+        return Err(Error::Malformed("Malformed macrocell node"));
+    }
+    let level = *level as u8;
+    let mut resolved = [0; 4];
+    for (child, index) in resolved.iter_mut().zip(children) {
+        *child = match index {
+            0 => universe.empty_node(level - 1),
+            i => *nodes
+                .get(*i)
+                .ok_or(Error::Malformed("Macrocell node refers to a later node"))?,
+        };
+        if universe.node_level(*child) != level - 1 {
+            return Err(Error::Malformed(
+                "Macrocell node has children of the wrong size",
+            ));
+        }
+    }
+    Ok(universe.join(resolved))
+}
+
+fn read_leaf(universe: &mut HashLife, line: &str) -> Result<NodeId, Error> {
+    let mut cells = [[false; 8]; 8];
+    let (mut x, mut y) = (0, 0);
+    for c in line.chars() {
+        match c {
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            '.' | '*' => {
+                if x >= 8 || y >= 8 {
+                    return Err(Error::Malformed("Macrocell leaf is larger than 8x8"));
+                }
+                cells[y][x] = c == '*';
+                x += 1;
+            }
+            _ => return Err(Error::Malformed("Unexpected character in macrocell data")),
+        }
+    }
+    Ok(build_leaf(universe, &cells, LEAF_LEVEL, 0, 0))
+}
+
+fn build_leaf(
+    universe: &mut HashLife,
+    cells: &[[bool; 8]; 8],
+    level: u8,
+    x: usize,
+    y: usize,
+) -> NodeId {
+    if level == 0 {
+        return HashLife::cell_node(cells[y][x]);
+    }
+    let half = 1 << (level - 1);
+    let children = [(0, 0), (half, 0), (0, half), (half, half)]
+        .map(|(dx, dy)| build_leaf(universe, cells, level - 1, x + dx, y + dy));
+    universe.join(children)
+}
+
+/// Writes a macrocell file to output.
+pub fn write_mc(output: impl io::Write, universe: &HashLife) -> Result<(), Error> {
+    let mut output = io::BufWriter::new(output);
+    let mut contents = format!("{HEADER} (gol-rs)\n#R {}\n", universe.rule());
+    if universe.generation() != 0 {
+        contents.push_str(&format!("#G {}\n", universe.generation()));
+    }
+    let mut numbers = HashMap::new();
+    write_node(universe, universe.root(), &mut numbers, &mut contents);
+    // An empty universe still needs a root.
+    if numbers.is_empty() {
+        contents.push_str("$\n");
+    }
+    output
+        .write_all(contents.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(Error::Io)
+}
+
+/// Writes a macrocell and all of its children, returning its number.
+fn write_node(
+    universe: &HashLife,
+    id: NodeId,
+    numbers: &mut HashMap<NodeId, usize>,
+    contents: &mut String,
+) -> usize {
+    if universe.node_population(id) == 0 {
+        return 0;
+    }
+    if let Some(number) = numbers.get(&id) {
+        return *number;
+    }
+    let level = universe.node_level(id);
+    if level == LEAF_LEVEL {
+        let mut rows: Vec<String> = (0..8)
+            .map(|y| {
+                let row: String = (0..8)
+                    .map(|x| {
+                        if leaf_cell(universe, id, level, x, y) {
+                            '*'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                row.trim_end_matches('.').to_string()
+            })
+            .collect();
+        while rows.last().is_some_and(String::is_empty) {
+            rows.pop();
+        }
+        for row in rows {
+            contents.push_str(&row);
+            contents.push('$');
+        }
+    } else {
+        let children = universe
+            .node_children(id)
+            .map(|child| write_node(universe, child, numbers, contents));
+        contents.push_str(&format!(
+            "{level} {} {} {} {}",
+            children[0], children[1], children[2], children[3]
+        ));
+    }
+    contents.push('\n');
+    let number = numbers.len() + 1;
+    numbers.insert(id, number);
+    number
+}
+
+fn leaf_cell(universe: &HashLife, id: NodeId, level: u8, x: usize, y: usize) -> bool {
+    if level == 0 {
+        return id == HashLife::cell_node(true);
+    }
+    let half = 1 << (level - 1);
+    let quadrant = usize::from(x >= half) + 2 * usize::from(y >= half);
+    let child = universe.node_children(id)[quadrant];
+    leaf_cell(universe, child, level - 1, x % half, y % half)
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use crate::{error::Error, rule::Rule};
+
+const RULE: &str = "rule";
+const GENERATION: &str = "generation";
+const AUTHOR: &str = "author";
+const NAME: &str = "name";
+const ORIGIN: &str = "origin";
+
+/// Information about the board stored alongside it in a .gol file.
+///
+/// Entries this crate doesn't know about are kept in `other`, so they survive being read and
+/// written back out. So are rules that aren't life-like, under the `rule` key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// The rule the board is meant to be run with.
+    pub rule: Option<Rule>,
+    /// How many generations the board has been run for.
+    pub generation: Option<u64>,
+    /// The pattern's author.
+    pub author: Option<String>,
+    /// The pattern's name.
+    pub name: Option<String>,
+    /// Where the top left corner of the board lies in some larger coordinate system.
+    pub origin: Option<(i64, i64)>,
+    /// Any other entries, by key.
+    pub other: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    /// Whether there are no entries at all.
+    pub fn is_empty(&self) -> bool {
+        self == &Metadata::default()
+    }
+
+    /// Parses the entries of a metadata block. Each entry is a key, prefixed by its length as a
+    /// byte, followed by a value, prefixed by its length as a 32 bit big-endian integer. Both are
+    /// UTF-8.
+    pub(crate) fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let mut metadata = Metadata::default();
+        while let Some((key_len, rest)) = bytes.split_first() {
+            let (key, rest) = split(rest, *key_len as usize)?;
+            let (value_len, rest) = split(rest, 4)?;
+            let value_len = u32::from_be_bytes(value_len.try_into().expect("Slice is 4 bytes"));
+            let (value, rest) = split(rest, value_len as usize)?;
+            bytes = rest;
+
+            let key =
+                std::str::from_utf8(key).or(Err(Error::Malformed("Metadata key isn't UTF-8")))?;
+            let value = std::str::from_utf8(value)
+                .or(Err(Error::Malformed("Metadata value isn't UTF-8")))?;
+            match key {
+                // Rules that aren't life-like, such as Generations rules, are kept as they are.
+                RULE => match value.parse() {
+                    Ok(rule) => metadata.rule = Some(rule),
+                    Err(_) => {
+                        metadata.other.insert(key.to_string(), value.to_string());
+                    }
+                },
+                GENERATION => {
+                    metadata.generation = Some(
+                        value
+                            .parse()
+                            .or(Err(Error::Malformed("Invalid generation in metadata")))?,
+                    )
+                }
+                AUTHOR => metadata.author = Some(value.to_string()),
+                NAME => metadata.name = Some(value.to_string()),
+                ORIGIN => {
+                    let origin = value
+                        .split_once(',')
+                        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+                    metadata.origin =
+                        Some(origin.ok_or(Error::Malformed("Invalid origin in metadata"))?);
+                }
+                _ => {
+                    metadata.other.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// Encodes the entries of a metadata block. Entries in `other` are left out when a field with
+    /// the same key is set, so each key is written at most once.
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let known = [
+            (RULE, self.rule.map(|r| r.to_string())),
+            (GENERATION, self.generation.map(|g| g.to_string())),
+            (AUTHOR, self.author.clone()),
+            (NAME, self.name.clone()),
+            (ORIGIN, self.origin.map(|(x, y)| format!("{x},{y}"))),
+        ];
+        let known: Vec<_> = known
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|v| (key.to_string(), v)))
+            .collect();
+        let other = self
+            .other
+            .iter()
+            .filter(|(k, _)| !known.iter().any(|(key, _)| key == *k))
+            .map(|(k, v)| (k.clone(), v.clone()));
+
+        let mut bytes = Vec::new();
+        for (key, value) in known.iter().cloned().chain(other) {
+            let key_len = u8::try_from(key.len())
+                .or(Err(Error::Unsupported("Metadata keys over 255 bytes")))?;
+            let value_len = u32::try_from(value.len())
+                .or(Err(Error::Unsupported("Metadata values over 4GiB")))?;
+            bytes.push(key_len);
+            bytes.extend_from_slice(key.as_bytes());
+            bytes.extend_from_slice(&value_len.to_be_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+        }
+        Ok(bytes)
+    }
+}
+
+fn split(bytes: &[u8], at: usize) -> Result<(&[u8], &[u8]), Error> {
+    if bytes.len() < at {
+        Err(Error::Malformed("Metadata block is truncated"))
+    } else {
+        Ok(bytes.split_at(at))
+    }
+}
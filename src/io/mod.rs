@@ -8,76 +8,385 @@
 //! a 0 (for dead) or 1 (for alive), and there should be width * height bytes.
 //!
 //! There is no terminator for the file.
+//!
+//! ## Version 2
+//! Version 2 files begin with the ASCII values for GOFV instead, followed by a version byte (2), a
+//! flags byte, a states byte and an extension byte. The width and height come next as in
+//! version 1, making for a 16 byte header.
+//!
+//! The extension byte gives the length of an extension between the header and the cells, in
+//! units of 256 bytes, so readers can always find the first cell. Bits 0 to 3 of the flags change
+//! how the cells are stored, and readers must reject files with any they don't understand. Bits 4
+//! to 7 only describe what is in the extension, and readers that don't understand them can skip
+//! the extension and ignore them.
+//!
+//! When bit 0 of the flags is set, which it always is for two state boards written by this crate,
+//! each cell is stored as a single bit. Every row is padded to a whole number of bytes, and within a byte
+//! the most significant bit is the leftmost cell. Otherwise cells are stored one per byte, as in
+//! version 1.
+//!
+//! When bit 4 of the flags is set, the extension holds a metadata block. It starts with its
+//! length in bytes as a 32 bit big-endian integer, and the rest of the extension after it is
+//! zero padding. The block is a series of key/value entries, see [Metadata] for the keys this
+//! crate understands. Entries with unknown keys are preserved.
+//!
+//! When bit 2 of the flags is set, cells can be in more than two states, as used by
+//! [Generations](crate::generations::Generations) rules. The states byte holds the number of
+//! states minus one, and each cell is stored as a byte holding its state, so bit 0 must be clear.
+//! Reading such a file as a two state board treats every cell not in state 1 as dead.
+//! [ReversibleLife](crate::reversible::ReversibleLife) boards are saved with four states, so that
+//! each cell holds both its current and its previous generation.
+//!
+//! ## Streaming
+//! Boards too large to hold in memory can be run straight from one .gol file to another with
+//! [transition_gol], which only keeps three rows in memory at a time. [run_gol] runs several
+//! generations by ping-ponging between two files.
+//!
+//! # Other Formats
+//! Readers and writers for the pattern formats used by the wider Life community live in the
+//! submodules. [read_pattern] detects which format some data is in and reads it accordingly.
 
 pub mod gol_file;
+mod header;
+pub mod life105;
+pub mod life106;
+pub mod macrocell;
+mod metadata;
+pub mod plaintext;
+pub mod rle;
+mod stream;
 
 pub use gol_file::GoLFile;
+pub use header::{Header, Version};
+pub use life105::{read_life105, write_life105};
+pub use life106::{read_life106, write_life106};
+pub use macrocell::{read_mc, write_mc};
+pub use metadata::Metadata;
+pub use plaintext::{read_cells, write_cells};
+pub use rle::{read_rle, write_rle};
+pub use stream::{run_gol, transition_gol};
 
 use std::io::{self, Write};
 
+use crate::error::Error;
 use crate::game_of_life::GameOfLife;
+use crate::generations::{Generations, GenerationsLife};
+use crate::grid::Grid;
 use crate::read::Read;
+use crate::reversible::ReversibleLife;
+use crate::write::Write as GoLWrite;
+
+/// The comments that text based pattern formats can carry alongside the board.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Comments {
+    /// The pattern's name.
+    pub name: Option<String>,
+    /// The pattern's author.
+    pub author: Option<String>,
+    /// Any other comments.
+    pub comments: Vec<String>,
+}
+
+/// The most cells a pattern read from a file can cover, since the whole board is allocated up
+/// front.
+const MAX_CELLS: usize = 1 << 30;
+
+/// Create a GameOfLife by reading data in any of the supported formats, detecting the format
+/// from the leading bytes.
+pub fn read_pattern(mut input: impl io::Read) -> Result<GameOfLife, Error> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let leading = &data[start..];
+    if data.starts_with(b"GOFL") || data.starts_with(b"GOFV") {
+        read_gol(data.as_slice())
+    } else if leading.starts_with(life106::HEADER.as_bytes()) {
+        read_life106(leading)
+    } else if leading.starts_with(life105::HEADER.as_bytes()) {
+        read_life105(leading)
+    } else if leading.starts_with(b"[M2]") {
+        let universe = read_mc(leading)?;
+        if let Some((min_x, min_y, max_x, max_y)) = universe.bounding_box() {
+            cropped_size(min_x, min_y, max_x, max_y)?;
+        }
+        Ok(GameOfLife::from(&universe))
+    } else if leading.starts_with(b"#") || leading.starts_with(b"x") {
+        read_rle(leading)
+    } else if leading.starts_with(b"!") || leading.starts_with(b".") || leading.starts_with(b"O") {
+        read_cells(leading)
+    } else {
+        Err(Error::BadMagic)
+    }
+}
+
+/// The width and height of a board cropped to the inclusive bounds. Fails if it would have more
+/// than [MAX_CELLS] cells.
+fn cropped_size(min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> Result<(usize, usize), Error> {
+    let width = max_x.abs_diff(min_x).checked_add(1);
+    let height = max_y.abs_diff(min_y).checked_add(1);
+    match (width, height) {
+        (Some(width), Some(height))
+            if width
+                .checked_mul(height)
+                .is_some_and(|cells| cells <= MAX_CELLS as u64) =>
+        {
+            Ok((width as usize, height as usize))
+        }
+        _ => Err(Error::Unsupported("Patterns with over 2^30 cells")),
+    }
+}
+
+/// Builds a board from the coordinates of live cells, cropped to their bounding box. Fails if
+/// the cells are too far apart.
+fn crop_to_cells(cells: &[(i64, i64)]) -> Result<GameOfLife, Error> {
+    let (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) = (
+        cells.iter().map(|c| c.0).min(),
+        cells.iter().map(|c| c.1).min(),
+        cells.iter().map(|c| c.0).max(),
+        cells.iter().map(|c| c.1).max(),
+    ) else {
+        return Ok(GameOfLife::from(Vec::new()));
+    };
+    let (width, height) = cropped_size(min_x, min_y, max_x, max_y)?;
+    let mut rows = vec![vec![false; width]; height];
+    for (x, y) in cells {
+        rows[y.abs_diff(min_y) as usize][x.abs_diff(min_x) as usize] = true;
+    }
+    Ok(rows.into())
+}
+
+/// Create a GameOfLife by reading .gol formatted data. Any version of the format can be read,
+/// and the board takes on the rule from the file's metadata, if it has one.
+pub fn read_gol(input: impl io::Read) -> Result<GameOfLife, Error> {
+    read_gol_with_metadata(input).map(|(board, _)| board)
+}
+
+/// Create a GameOfLife by reading .gol formatted data, also returning the file's metadata.
+pub fn read_gol_with_metadata(mut input: impl io::Read) -> Result<(GameOfLife, Metadata), Error> {
+    let header = Header::read(&mut input)?;
+    let metadata = header.read_metadata(&mut input)?;
+    let body = header.read_body(&mut input)?;
+    let mut result = Vec::with_capacity(header.height);
+    for bytes in body.chunks(header.row_bytes().max(1)).take(header.height) {
+        let mut row = vec![false; header.width];
+        header.decode_cells(0, bytes, &mut row);
+        result.push(row);
+    }
+    let board = GameOfLife::from(result).with_rule(metadata.rule.unwrap_or_default());
+    Ok((board, metadata))
+}
+
+/// Create a GenerationsLife by reading .gol formatted data. Two state files are read with dead
+/// and alive cells, and the board takes on the Generations rule from the file's metadata, if it
+/// has one.
+pub fn read_gol_generations(mut input: impl io::Read) -> Result<GenerationsLife, Error> {
+    let header = Header::read(&mut input)?;
+    let metadata = header.read_metadata(&mut input)?;
+    let body = header.read_body(&mut input)?;
+    let mut grid = Grid::new(header.width, header.height);
+    let mut row = vec![0; header.width];
+    for (y, bytes) in body
+        .chunks(header.row_bytes().max(1))
+        .take(header.height)
+        .enumerate()
+    {
+        header.decode_states(0, bytes, &mut row);
+        grid.write_row(0, y, &row)?;
+    }
+    let rule = match (metadata.other.get("rule"), metadata.rule) {
+        (Some(rule), _) => rule.parse()?,
+        (None, Some(rule)) => Generations::new(rule, 2)?,
+        (None, None) => Generations::default(),
+    };
+    let rule = Generations::new(rule.rule(), rule.states().max(header.states()))?;
+    GenerationsLife::from(grid).with_rule(rule)
+}
 
-/// Create a GameOfLife by reading .gol formatted data.
-pub fn read_gol(mut input: impl io::Read) -> Result<GameOfLife, &'static str> {
-    let (width, height) = {
-        let mut header = [0; 12];
-        input
-            .read_exact(&mut header)
-            .or(Err("Failed to read header"))?;
-        if !header.starts_with(b"GOFL") {
-            return Err("File isn't a Game of Life file");
+/// Writes a version 2 .gol file to output, storing each cell's state and the board's rule.
+pub fn write_gol_generations(output: impl io::Write, state: &GenerationsLife) -> Result<(), Error> {
+    let header = Header::new(Version::V2, state.width(), state.height())
+        .with_states(state.rule().states())?;
+    let mut metadata = Metadata::default();
+    metadata
+        .other
+        .insert("rule".to_string(), state.rule().to_string());
+    let mut output = io::BufWriter::new(output);
+    let header = write_gol_header(&mut output, header, &metadata)?;
+    let mut bytes = vec![0; header.row_bytes()];
+    let mut cells = state.iter();
+    for _ in 0..header.height {
+        if header.is_packed() {
+            let row: Vec<bool> = cells.by_ref().take(header.width).map(|s| s == 1).collect();
+            header.encode_cells(0, &row, &mut bytes);
+        } else {
+            bytes
+                .iter_mut()
+                .zip(cells.by_ref())
+                .for_each(|(byte, state)| *byte = state);
         }
-        let w = u32::from_be_bytes(header[4..8].try_into().expect("File should be long enough"))
-            as usize;
-        let h = u32::from_be_bytes(
-            header[8..12]
-                .try_into()
-                .expect("File should be long enough"),
-        ) as usize;
-        (w, h)
+        output.write_all(&bytes)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// Create a ReversibleLife by reading .gol formatted data. Cells in four state files hold both
+/// generations, 1 for cells alive in the current generation, 2 for cells alive in the previous
+/// one and 3 for both. Two state files are read as the current generation, with every cell of the
+/// previous one dead.
+pub fn read_gol_reversible(mut input: impl io::Read) -> Result<ReversibleLife, Error> {
+    let header = Header::read(&mut input)?;
+    let metadata = header.read_metadata(&mut input)?;
+    if header.states() > 4 {
+        return Err(Error::Unsupported(
+            "Reversible boards with more than four states",
+        ));
+    }
+    let body = header.read_body(&mut input)?;
+    let mut current = Vec::with_capacity(header.height);
+    let mut previous = Vec::with_capacity(header.height);
+    let mut row = vec![0; header.width];
+    for bytes in body.chunks(header.row_bytes().max(1)).take(header.height) {
+        header.decode_states(0, bytes, &mut row);
+        current.push(row.iter().map(|state| state & 1 != 0).collect::<Vec<_>>());
+        previous.push(row.iter().map(|state| state & 2 != 0).collect::<Vec<_>>());
+    }
+    let current = GameOfLife::from(current).with_rule(metadata.rule.unwrap_or_default());
+    ReversibleLife::from_generations(current, &GameOfLife::from(previous))
+}
+
+/// Write a ReversibleLife to .gol formatted data as a four state board, keeping both generations
+/// and the rule.
+pub fn write_gol_reversible(output: impl io::Write, state: &ReversibleLife) -> Result<(), Error> {
+    let header = Header::new(Version::V2, state.width(), state.height()).with_states(4)?;
+    let metadata = Metadata {
+        rule: Some(state.rule()),
+        ..Metadata::default()
     };
-    let mut result = Vec::with_capacity(height);
-    let mut buffer = vec![0; width];
-    while let Ok(()) = input.read_exact(&mut buffer) {
-        result.push(buffer.iter().map(|i| *i == 1).collect())
+    let mut output = io::BufWriter::new(output);
+    let header = write_gol_header(&mut output, header, &metadata)?;
+    let mut bytes = vec![0; header.row_bytes()];
+    let mut cells = state.current().iter().zip(state.previous().iter());
+    for _ in 0..header.height {
+        bytes
+            .iter_mut()
+            .zip(cells.by_ref())
+            .for_each(|(byte, (current, previous))| {
+                *byte = u8::from(current) | u8::from(previous) << 1
+            });
+        output.write_all(&bytes)?;
     }
-    Ok(result.into())
+    output.flush()?;
+    Ok(())
+}
+
+/// Writes a version 1 .gol file to output.
+pub fn write_gol(output: impl io::Write, state: &GameOfLife) -> Result<(), Error> {
+    write_gol_with_version(output, state, Version::V1)
 }
 
-/// Writes a .gol file to output.
-pub fn write_gol(mut output: impl io::Write, state: &GameOfLife) -> Result<(), &'static str> {
-    let width = state.width() as u32;
-    let height = state.height() as u32;
-    let mut output_vec = Vec::with_capacity(12 + width as usize * height as usize);
-    output_vec.extend_from_slice(b"GOFL");
-    output_vec.extend_from_slice(&width.to_be_bytes());
-    output_vec.extend_from_slice(&height.to_be_bytes());
-    output_vec.extend(state.iter().map(u8::from));
-    output
-        .write_all(&output_vec)
-        .or(Err("Failed writing the data"))
+/// Writes a .gol file of the provided version to output.
+pub fn write_gol_with_version(
+    output: impl io::Write,
+    state: &GameOfLife,
+    version: Version,
+) -> Result<(), Error> {
+    write_gol_iterator_with_version(
+        output,
+        state.width() as u32,
+        state.height() as u32,
+        state.iter(),
+        version,
+    )
 }
 
-/// Writes a .gol file to output, but using an iterator as input. This is useful for [GoLFile]
-/// and the [TransitionIter](crate::iter::TransitionIter)
+/// Writes a version 1 .gol file to output, but using an iterator as input. This is useful for
+/// [GoLFile] and the [TransitionIter](crate::iter::TransitionIter)
+///
+/// Fails with [Error::DimensionMismatch] if the iterator doesn't yield exactly `width * height`
+/// cells.
 pub fn write_gol_iterator(
     output: impl io::Write,
     width: u32,
     height: u32,
     data: impl Iterator<Item = bool>,
-) -> Result<(), &'static str> {
+) -> Result<(), Error> {
+    write_gol_iterator_with_version(output, width, height, data, Version::V1)
+}
+
+/// Writes a version 2 .gol file to output, including the provided metadata.
+pub fn write_gol_with_metadata(
+    output: impl io::Write,
+    state: &GameOfLife,
+    metadata: &Metadata,
+) -> Result<(), Error> {
+    let header = Header::new(Version::V2, state.width(), state.height());
+    write_gol_data(output, header, metadata, state.iter())
+}
+
+/// Writes a .gol file of the provided version to output, but using an iterator as input.
+pub fn write_gol_iterator_with_version(
+    output: impl io::Write,
+    width: u32,
+    height: u32,
+    data: impl Iterator<Item = bool>,
+    version: Version,
+) -> Result<(), Error> {
+    let header = Header::new(version, width as usize, height as usize);
+    write_gol_data(output, header, &Metadata::default(), data)
+}
+
+fn write_gol_data(
+    output: impl io::Write,
+    header: Header,
+    metadata: &Metadata,
+    mut data: impl Iterator<Item = bool>,
+) -> Result<(), Error> {
     let mut output = io::BufWriter::new(output);
-    output
-        .write(b"GOFL")
-        .and_then(|_| output.write(&width.to_be_bytes()))
-        .and_then(|_| output.write(&height.to_be_bytes()))
-        .and_then(|_| {
-            data.map(|b| output.write(&[u8::from(b)]))
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .and(Ok(()))
-        .or(Err("Failed to write file"))
+    let header = write_gol_header(&mut output, header, metadata)?;
+    let mut row = Vec::with_capacity(header.width);
+    let mut bytes = Vec::with_capacity(header.row_bytes());
+    for _ in 0..header.height {
+        row.clear();
+        row.extend(data.by_ref().take(header.width));
+        if row.len() < header.width {
+            return Err(Error::DimensionMismatch);
+        }
+        bytes.clear();
+        header.encode_row(&row, &mut bytes);
+        output.write_all(&bytes).map_err(Error::Io)?;
+    }
+    if data.next().is_some() {
+        return Err(Error::DimensionMismatch);
+    }
+    output.flush().map_err(Error::Io)
+}
+
+/// Writes the header and its extension holding the metadata block, returning the header as
+/// written.
+fn write_gol_header(
+    output: &mut impl io::Write,
+    header: Header,
+    metadata: &Metadata,
+) -> Result<Header, Error> {
+    let mut metadata_bytes = Vec::new();
+    let header = if metadata.is_empty() {
+        header
+    } else {
+        metadata_bytes = metadata.to_bytes()?;
+        header.with_metadata_len(metadata_bytes.len())?
+    };
+    output.write_all(&header.to_bytes())?;
+    if header.has_metadata() {
+        let padding = header.extension_len() - 4 - metadata_bytes.len();
+        output.write_all(&(metadata_bytes.len() as u32).to_be_bytes())?;
+        output.write_all(&metadata_bytes)?;
+        output.write_all(&vec![0; padding])?;
+    }
+    Ok(header)
 }
 
 #[allow(unused)]
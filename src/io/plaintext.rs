@@ -0,0 +1,79 @@
+//! # Plaintext File Format
+//! Plaintext (.cells) files draw the board directly, one line per row, with `.` for dead cells and
+//! `O` for live cells. Lines starting with `!` are comments, where `!Name:` and `!Author:` give
+//! the pattern's name and author. Dead cells at the end of a row may be omitted.
+
+use std::io::{self, Write};
+
+use super::Comments;
+use crate::{error::Error, game_of_life::GameOfLife, read::Read};
+
+/// Create a GameOfLife by reading plaintext formatted data.
+pub fn read_cells(input: impl io::Read) -> Result<GameOfLife, Error> {
+    read_cells_with_comments(input).map(|(board, _)| board)
+}
+
+/// Create a GameOfLife by reading plaintext formatted data, also returning the file's comments.
+pub fn read_cells_with_comments(mut input: impl io::Read) -> Result<(GameOfLife, Comments), Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    let mut comments = Comments::default();
+    let mut rows = Vec::new();
+    for line in contents.lines().map(|l| l.trim_end()) {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(name) = comment.strip_prefix("Name:") {
+                comments.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                comments.author = Some(author.trim().to_string());
+            } else {
+                comments.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        let row = line
+            .chars()
+            .map(|c| match c {
+                '.' => Ok(false),
+                'O' | '*' => Ok(true),
+                _ => Err(Error::Malformed("Unexpected character in plaintext data")),
+            })
+            .collect::<Result<Vec<bool>, _>>()?;
+        rows.push(row);
+    }
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    rows.iter_mut().for_each(|row| row.resize(width, false));
+    Ok((GameOfLife::from(rows), comments))
+}
+
+/// Writes a plaintext file to output.
+pub fn write_cells(output: impl io::Write, state: &GameOfLife) -> Result<(), Error> {
+    write_cells_with_comments(output, state, &Comments::default())
+}
+
+/// Writes a plaintext file to output, starting with the provided comments.
+pub fn write_cells_with_comments(
+    output: impl io::Write,
+    state: &GameOfLife,
+    comments: &Comments,
+) -> Result<(), Error> {
+    let mut output = io::BufWriter::new(output);
+    let mut contents = String::new();
+    if let Some(name) = &comments.name {
+        contents.push_str(&format!("!Name: {name}\n"));
+    }
+    if let Some(author) = &comments.author {
+        contents.push_str(&format!("!Author: {author}\n"));
+    }
+    for comment in &comments.comments {
+        contents.push_str(&format!("!{comment}\n"));
+    }
+    let cells: Vec<bool> = state.iter().collect();
+    for row in cells.chunks(state.width().max(1)) {
+        contents.extend(row.iter().map(|c| if *c { 'O' } else { '.' }));
+        contents.push('\n');
+    }
+    output
+        .write_all(contents.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(Error::Io)
+}
@@ -0,0 +1,247 @@
+//! # RLE File Format
+//! Run Length Encoded (.rle) files are the most common way Life patterns are shared.
+//!
+//! Any number of `#` comment lines may come first. `#N` holds the pattern's name, `#O` its author
+//! and `#C` (or `#c`) general comments. Next is a header line such as `x = 3, y = 3, rule = B3/S23`
+//! giving the width and height of the pattern and optionally its rule. A rule may end with a
+//! bounded grid suffix like `:T3,3`, which is used to pick the [Topology].
+//!
+//! The cells follow as a series of `<run_count><tag>` items, where the run count defaults to 1.
+//! `b` is a dead cell, `o` is a live cell, `$` ends a row and `!` ends the pattern. Dead cells at
+//! the end of a row and empty rows at the end of the pattern are omitted.
+
+use std::io::{self, Write};
+
+use super::{Comments, MAX_CELLS};
+use crate::{error::Error, game_of_life::GameOfLife, read::Read, rule::Rule, topology::Topology};
+
+/// The longest line written by [write_rle].
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Returned when runs take a position past the largest possible board.
+const OVERFLOW: Error = Error::Malformed("RLE runs go past the largest possible board");
+
+/// Create a GameOfLife by reading RLE formatted data. The board takes on the rule and topology
+/// from the header.
+pub fn read_rle(input: impl io::Read) -> Result<GameOfLife, Error> {
+    read_rle_with_comments(input).map(|(board, _)| board)
+}
+
+/// Create a GameOfLife by reading RLE formatted data, also returning the file's comments.
+pub fn read_rle_with_comments(mut input: impl io::Read) -> Result<(GameOfLife, Comments), Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    let mut comments = Comments::default();
+    let mut rule = Rule::default();
+    let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+    let header = loop {
+        let line = lines
+            .next()
+            .ok_or(Error::Malformed("RLE data is missing its header"))?;
+        match line.strip_prefix('#') {
+            Some(comment) => {
+                let (kind, text) =
+                    comment.split_at(comment.chars().next().map_or(0, char::len_utf8));
+                let text = text.trim().to_string();
+                match kind {
+                    "N" => comments.name = Some(text),
+                    "O" => comments.author = Some(text),
+                    "C" | "c" => comments.comments.push(text),
+                    "r" => rule = text.parse()?,
+                    _ => {}
+                }
+            }
+            None => break line,
+        }
+    };
+
+    let (width, height, topology) = {
+        let mut width: Option<usize> = None;
+        let mut height: Option<usize> = None;
+        let mut topology = Topology::Bounded;
+        // The dimensions of a bounded grid are separated by a comma within the rule, so fields
+        // without a key belong to the one before them.
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        for field in header.split(',') {
+            match (field.split_once('='), fields.last_mut()) {
+                (Some((key, value)), _) => fields.push((key.trim(), value.trim().to_string())),
+                (None, Some((_, value))) => value.push_str(&format!(",{}", field.trim())),
+                (None, None) => return Err(Error::Malformed("Malformed RLE header")),
+            }
+        }
+        for (key, value) in &fields {
+            let value = value.as_str();
+            match *key {
+                "x" => {
+                    width = Some(
+                        value
+                            .parse()
+                            .or(Err(Error::Malformed("Invalid RLE width")))?,
+                    )
+                }
+                "y" => {
+                    height = Some(
+                        value
+                            .parse()
+                            .or(Err(Error::Malformed("Invalid RLE height")))?,
+                    )
+                }
+                "rule" => {
+                    let (rulestring, grid) = value.split_once(':').unwrap_or((value, ""));
+                    rule = rulestring.parse()?;
+                    topology = parse_grid(grid)?;
+                }
+                _ => return Err(Error::Malformed("Malformed RLE header")),
+            }
+        }
+        match (width, height) {
+            (Some(w), Some(h)) => (w, h, topology),
+            _ => {
+                return Err(Error::Malformed(
+                    "RLE header is missing the width or height",
+                ))
+            }
+        }
+    };
+
+    if width
+        .checked_mul(height)
+        .is_none_or(|cells| cells > MAX_CELLS)
+    {
+        return Err(Error::Unsupported("RLE patterns with over 2^30 cells"));
+    }
+    let mut cells = vec![vec![false; width]; height];
+    let (mut x, mut y): (usize, usize) = (0, 0);
+    let mut run = None::<usize>;
+    'lines: for line in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                let count = run
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|r| r.checked_add(digit as usize));
+                run = Some(count.ok_or(Error::Malformed("RLE run count is too large"))?);
+                continue;
+            }
+            let count = run.take().unwrap_or(1);
+            match c {
+                '!' => break 'lines,
+                '$' => {
+                    y = y.checked_add(count).ok_or(OVERFLOW)?;
+                    x = 0;
+                }
+                'b' | '.' => x = x.checked_add(count).ok_or(OVERFLOW)?,
+                c if c.is_ascii_alphabetic() => {
+                    let end = x.checked_add(count).ok_or(OVERFLOW)?;
+                    if end > width || y >= height {
+                        return Err(Error::Malformed("RLE pattern is larger than its header"));
+                    }
+                    cells[y][x..end].fill(true);
+                    x = end;
+                }
+                c if c.is_whitespace() => {}
+                _ => return Err(Error::Malformed("Unexpected character in RLE data")),
+            }
+        }
+    }
+
+    let board = GameOfLife::from(cells)
+        .with_rule(rule)
+        .with_topology(topology);
+    Ok((board, comments))
+}
+
+/// Writes an RLE file to output.
+pub fn write_rle(output: impl io::Write, state: &GameOfLife) -> Result<(), Error> {
+    write_rle_with_comments(output, state, &Comments::default())
+}
+
+/// Writes an RLE file to output, starting with the provided comments.
+pub fn write_rle_with_comments(
+    output: impl io::Write,
+    state: &GameOfLife,
+    comments: &Comments,
+) -> Result<(), Error> {
+    let mut output = io::BufWriter::new(output);
+    let mut contents = String::new();
+    if let Some(name) = &comments.name {
+        contents.push_str(&format!("#N {name}\n"));
+    }
+    if let Some(author) = &comments.author {
+        contents.push_str(&format!("#O {author}\n"));
+    }
+    for comment in &comments.comments {
+        contents.push_str(&format!("#C {comment}\n"));
+    }
+    contents.push_str(&format!(
+        "x = {}, y = {}, rule = {}{}\n",
+        state.width(),
+        state.height(),
+        state.rule(),
+        grid_suffix(state),
+    ));
+
+    let mut line = String::new();
+    let mut push_item = |count: usize, tag: char, contents: &mut String| {
+        let item = match count {
+            1 => tag.to_string(),
+            _ => format!("{count}{tag}"),
+        };
+        if line.len() + item.len() > MAX_LINE_LENGTH {
+            contents.push_str(&line);
+            contents.push('\n');
+            line.clear();
+        }
+        line.push_str(&item);
+    };
+    let mut pending_rows = 0;
+    let cells: Vec<bool> = state.iter().collect();
+    for row in cells.chunks(state.width().max(1)) {
+        let Some(last_alive) = row.iter().rposition(|c| *c) else {
+            pending_rows += 1;
+            continue;
+        };
+        if pending_rows > 0 {
+            push_item(pending_rows, '$', &mut contents);
+        }
+        for run in row[..=last_alive].chunk_by(|a, b| a == b) {
+            push_item(run.len(), if run[0] { 'o' } else { 'b' }, &mut contents);
+        }
+        pending_rows = 1;
+    }
+    push_item(1, '!', &mut contents);
+    contents.push_str(&line);
+    contents.push('\n');
+
+    output
+        .write_all(contents.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(Error::Io)
+}
+
+/// Parses the bounded grid suffix of a rule, such as the `T10,10` in `B3/S23:T10,10`.
+fn parse_grid(grid: &str) -> Result<Topology, Error> {
+    let grid = grid.trim();
+    match grid.chars().next().map(|c| c.to_ascii_uppercase()) {
+        None | Some('P') => Ok(Topology::Bounded),
+        Some('T') => Ok(Topology::Torus),
+        Some('C') => Ok(Topology::CrossSurface),
+        Some('K') => match grid.split_once(',') {
+            Some((width, _)) if width.ends_with('*') => Ok(Topology::KleinBottle),
+            _ => Err(Error::Unsupported(
+                "Klein bottles with twisted left and right edges",
+            )),
+        },
+        _ => Err(Error::Unsupported("Bounded grid type in RLE rule")),
+    }
+}
+
+fn grid_suffix(state: &GameOfLife) -> String {
+    let (w, h) = (state.width(), state.height());
+    match state.topology() {
+        Topology::Bounded => String::new(),
+        Topology::Torus => format!(":T{w},{h}"),
+        Topology::KleinBottle => format!(":K{w}*,{h}"),
+        Topology::CrossSurface => format!(":C{w},{h}"),
+    }
+}
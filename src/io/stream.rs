@@ -0,0 +1,130 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use super::{write_gol_header, Header, Version};
+use crate::error::Error;
+
+/// Reads a board from .gol formatted input and writes its next generation to output, in the same
+/// version of the format.
+///
+/// Only a sliding window of three rows is kept in memory, so memory use depends on the width of
+/// the board but not its height. The board is bounded, and runs under the rule from the input's
+/// metadata, or [Rule::CONWAY](crate::rule::Rule::CONWAY) if there isn't one. The metadata is
+/// copied to version 2 output with its generation count incremented.
+///
+/// Since only the adjacent rows are available, rules whose neighborhood reaches further aren't
+/// supported.
+pub fn transition_gol(input: impl io::Read, output: impl io::Write) -> Result<(), Error> {
+    let mut input = io::BufReader::new(input);
+    let mut output = io::BufWriter::new(output);
+    let header = Header::read(&mut input)?;
+    if header.states() != 2 {
+        return Err(Error::Unsupported(
+            "Streaming boards with more than two states",
+        ));
+    }
+    let mut metadata = header.read_metadata(&mut input)?;
+    let rule = metadata.rule.unwrap_or_default();
+    if rule.neighborhood().radius() > 1 {
+        return Err(Error::Unsupported(
+            "Streaming rules with a neighborhood radius over 1",
+        ));
+    }
+    let offsets: Vec<_> = rule.neighborhood().offsets().collect();
+    if header.version == Version::V2 {
+        metadata.generation = Some(metadata.generation.unwrap_or(0) + 1);
+    }
+    let out_header = write_gol_header(
+        &mut output,
+        Header::new(header.version, header.width, header.height),
+        &metadata,
+    )?;
+
+    let width = header.width;
+    let mut bytes = vec![0; header.row_bytes()];
+    let mut above = vec![false; width];
+    let mut current = vec![false; width];
+    let mut below = vec![false; width];
+    let mut next = vec![false; width];
+    let mut encoded = Vec::with_capacity(out_header.row_bytes());
+    if header.height > 0 {
+        read_row(&mut input, &header, 0, &mut bytes, &mut current)?;
+    }
+    for y in 0..header.height {
+        if y + 1 < header.height {
+            read_row(&mut input, &header, y + 1, &mut bytes, &mut below)?;
+        } else {
+            below.fill(false);
+        }
+        for x in 0..width {
+            let count = offsets
+                .iter()
+                .filter(|(dx, dy)| {
+                    let row = match dy {
+                        -1 => &above,
+                        0 => &current,
+                        _ => &below,
+                    };
+                    x.checked_add_signed(*dx)
+                        .and_then(|x| row.get(x))
+                        .is_some_and(|alive| *alive)
+                })
+                .count();
+            next[x] = rule.next_state(current[x], count);
+        }
+        encoded.clear();
+        out_header.encode_row(&next, &mut encoded);
+        output.write_all(&encoded)?;
+
+        // Slide the window down a row, reusing the oldest row's storage.
+        std::mem::swap(&mut above, &mut current);
+        std::mem::swap(&mut current, &mut below);
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// Runs a board for several generations, ping-ponging between two files so that only three rows
+/// are ever in memory. The board is read from the start of `source`, and `scratch` is overwritten
+/// from its start. Returns whichever of the two holds the final generation.
+///
+/// Neither file is truncated, so if it was previously longer there will be stale bytes after the
+/// board. Readers of the .gol format ignore them.
+pub fn run_gol<'a, F: io::Read + io::Write + Seek>(
+    mut source: &'a mut F,
+    mut scratch: &'a mut F,
+    generations: u64,
+) -> Result<&'a mut F, Error> {
+    for _ in 0..generations {
+        source.seek(SeekFrom::Start(0))?;
+        scratch.seek(SeekFrom::Start(0))?;
+        transition_gol(&mut *source, &mut *scratch)?;
+        std::mem::swap(&mut source, &mut scratch);
+    }
+    Ok(source)
+}
+
+/// Reads and decodes row `y` of the board, reporting a truncated body if the input runs out.
+fn read_row(
+    input: &mut impl Read,
+    header: &Header,
+    y: usize,
+    bytes: &mut [u8],
+    row: &mut [bool],
+) -> Result<(), Error> {
+    let mut got = 0;
+    while got < bytes.len() {
+        match input.read(&mut bytes[got..]) {
+            Ok(0) => {
+                return Err(Error::TruncatedBody {
+                    expected: header.body_len()?,
+                    got: header.row_bytes() * y + got,
+                })
+            }
+            Ok(n) => got += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    header.decode_cells(0, bytes, row);
+    Ok(())
+}
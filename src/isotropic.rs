@@ -0,0 +1,389 @@
+//! # Isotropic Non-Totalistic Rules
+//! Isotropic non-totalistic rules decide a cell's fate by the arrangement of its live neighbors,
+//! not just how many there are. Arrangements that are rotations or reflections of each other are
+//! treated the same, which splits the 256 possible neighborhoods into 51 configurations.
+//!
+//! Rules are written in Hensel notation, which follows each neighbor count in B/S notation with
+//! letters picking out some of its configurations, such as `B2-a/S12`. A count with no letters
+//! includes every configuration, and a `-` before the letters includes all but those listed.
+//! Counts 1 to 3 use the letters `c` (corners), `e` (edges), `a`, `i`, `k`, `n`, `j`, `q`, `r` and
+//! `y`, count 4 adds `t`, `w` and `z`, and counts 5 to 7 use the same letters as 3 to 1 for the
+//! inverted configuration, so `7c` is every neighbor except a corner.
+//!
+//! Neighborhoods are indexed with bit `dy * 3 + dx` holding the cell at offset `(dx - 1, dy - 1)`,
+//! so bit 4 is the cell itself, and every rule is compiled to a 512 entry table of next states.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    error::Error,
+    game_of_life::GameOfLife,
+    grid::Grid,
+    neighborhood::Neighborhood,
+    read::Read,
+    rule::{split_rulestring, Rule},
+    topology::Topology,
+    write::Write,
+};
+
+/// The letters of the configurations of each neighbor count, in the order of [CONFIGURATIONS].
+const LETTERS: [&str; 9] = [
+    "",
+    "ce",
+    "ceaikn",
+    "ceaiknjqry",
+    "ceaiknjqrytwz",
+    "ceaiknjqry",
+    "ceaikn",
+    "ce",
+    "",
+];
+
+/// A neighborhood in each configuration of up to 4 live neighbors, in the order of [LETTERS].
+/// Configurations of more neighbors are the inverses of these.
+const CONFIGURATIONS: [&[u16]; 5] = [
+    &[0],
+    &[1, 2],
+    &[5, 10, 3, 40, 33, 68],
+    &[69, 42, 11, 7, 98, 13, 14, 70, 41, 97],
+    &[325, 170, 15, 45, 99, 71, 106, 102, 43, 101, 105, 78, 108],
+];
+
+/// Every neighbor bit, leaving out the cell itself.
+const NEIGHBORS: u16 = 0b1_1110_1111;
+const CENTER: usize = 1 << 4;
+
+/// The bit of each neighbor returned by [Read::read_neighbors], clockwise from the top left.
+const NEIGHBOR_BITS: [usize; 8] = [0, 1, 2, 5, 8, 7, 6, 3];
+
+/// An isotropic non-totalistic ruleset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Isotropic {
+    /// The configurations of each neighbor count that cause a birth, bit `i` being the
+    /// configuration named by letter `i`.
+    birth: [u16; 9],
+    survival: [u16; 9],
+    /// Bit `i` is the next state of a cell whose neighborhood is `i`.
+    table: [u64; 8],
+}
+
+impl Isotropic {
+    fn from_conditions(birth: [u16; 9], survival: [u16; 9]) -> Self {
+        let mut table = [0; 8];
+        for index in 0..512 {
+            let (count, letter) = classify(index as u16 & NEIGHBORS);
+            let conditions = if index & CENTER != 0 {
+                &survival
+            } else {
+                &birth
+            };
+            if conditions[count] & (1 << letter) != 0 {
+                table[index / 64] |= 1 << (index % 64);
+            }
+        }
+        Isotropic {
+            birth,
+            survival,
+            table,
+        }
+    }
+
+    /// Returns the next state of a cell from its 3x3 neighborhood, indexed as described in the
+    /// [module documentation](self).
+    pub fn next_state(&self, neighborhood: usize) -> bool {
+        self.table[neighborhood / 64] & (1 << (neighborhood % 64)) != 0
+    }
+
+    /// Aliveness check, returns whether or not the provided cell survives, dies, or comes to life.
+    /// Neighbors are in the order of [Read::read_neighbors], and those that are `None` (i.e. off
+    /// the board) are treated as dead.
+    pub fn is_alive(&self, current: bool, neighbors: &[Option<bool>; 8]) -> bool {
+        let index = NEIGHBOR_BITS
+            .iter()
+            .zip(neighbors)
+            .filter(|(_, alive)| **alive == Some(true))
+            .fold(usize::from(current) * CENTER, |index, (bit, _)| {
+                index | 1 << bit
+            });
+        self.next_state(index)
+    }
+}
+
+impl Default for Isotropic {
+    /// Conway's Game of Life, `B3/S23`.
+    fn default() -> Self {
+        Rule::CONWAY
+            .try_into()
+            .expect("Conway's Game of Life is totalistic")
+    }
+}
+
+impl TryFrom<Rule> for Isotropic {
+    type Error = Error;
+
+    /// Converts a life-like rule, which must use the Moore neighborhood.
+    fn try_from(value: Rule) -> Result<Self, Self::Error> {
+        if value.neighborhood() != Neighborhood::Moore(1) {
+            return Err(Error::Unsupported(
+                "Isotropic rules over neighborhoods other than Moore",
+            ));
+        }
+        let conditions = |f: fn(&Rule, usize) -> bool| {
+            std::array::from_fn(|count| if f(&value, count) { all(count) } else { 0 })
+        };
+        Ok(Self::from_conditions(
+            conditions(Rule::is_birth),
+            conditions(Rule::is_survival),
+        ))
+    }
+}
+
+impl FromStr for Isotropic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (birth, survival) = split_rulestring(s.trim())?;
+        Ok(Self::from_conditions(
+            parse_conditions(birth)?,
+            parse_conditions(survival)?,
+        ))
+    }
+}
+
+impl fmt::Display for Isotropic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        write_conditions(f, &self.birth)?;
+        write!(f, "/S")?;
+        write_conditions(f, &self.survival)
+    }
+}
+
+/// Every configuration of a neighbor count.
+fn all(count: usize) -> u16 {
+    (1 << LETTERS[count].len().max(1)) - 1
+}
+
+/// Works out the neighbor count and configuration letter of a neighborhood.
+fn classify(neighbors: u16) -> (usize, usize) {
+    let count = neighbors.count_ones() as usize;
+    // Past 4 neighbors, the configuration is named after the inverse neighborhood.
+    let (lookup, shape) = if count > 4 {
+        (8 - count, canonical(!neighbors & NEIGHBORS))
+    } else {
+        (count, canonical(neighbors))
+    };
+    let letter = CONFIGURATIONS[lookup]
+        .iter()
+        .position(|configuration| canonical(*configuration) == shape)
+        .expect("Every neighborhood has a configuration");
+    (count, letter)
+}
+
+/// The smallest of the rotations and reflections of a neighborhood, which is the same for every
+/// neighborhood in a configuration.
+fn canonical(neighbors: u16) -> u16 {
+    let transform = |neighbors: u16, f: fn(u16, u16) -> (u16, u16)| {
+        (0..9)
+            .filter(|bit| neighbors & (1 << bit) != 0)
+            .map(|bit| f(bit % 3, bit / 3))
+            .fold(0, |out, (x, y)| out | 1 << (y * 3 + x))
+    };
+    let rotate = |x, y| (2 - y, x);
+    let reflect = |x, y| (2 - x, y);
+    let mut neighbors = neighbors;
+    let mut smallest = neighbors;
+    for _ in 0..4 {
+        neighbors = transform(neighbors, rotate);
+        smallest = smallest.min(neighbors).min(transform(neighbors, reflect));
+    }
+    smallest
+}
+
+fn parse_conditions(s: &str) -> Result<[u16; 9], Error> {
+    let mut conditions = [0; 9];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let count = c
+            .to_digit(10)
+            .filter(|count| *count <= 8)
+            .ok_or(Error::InvalidRule(
+                "Neighbor counts must be digits between 0 and 8",
+            ))? as usize;
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = 0;
+        while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+            let index = LETTERS[count]
+                .find(letter.to_ascii_lowercase())
+                .ok_or(Error::InvalidRule("Unknown configuration letter"))?;
+            letters |= 1 << index;
+        }
+        conditions[count] |= match (letters, negated) {
+            (0, true) => return Err(Error::InvalidRule("A '-' must be followed by letters")),
+            (0, false) => all(count),
+            (letters, true) => all(count) & !letters,
+            (letters, false) => letters,
+        };
+    }
+    Ok(conditions)
+}
+
+/// Writes each count with whichever of its letters or the letters it leaves out is shorter.
+fn write_conditions(f: &mut fmt::Formatter<'_>, conditions: &[u16; 9]) -> fmt::Result {
+    for (count, letters) in conditions.iter().enumerate() {
+        let missing = all(count) & !letters;
+        let (prefix, shown) = match (*letters, missing) {
+            (0, _) => continue,
+            (_, 0) => ("", 0),
+            _ if letters.count_ones() <= missing.count_ones() => ("", *letters),
+            _ => ("-", missing),
+        };
+        write!(f, "{count}{prefix}")?;
+        for (i, letter) in LETTERS[count].chars().enumerate() {
+            if shown & (1 << i) != 0 {
+                write!(f, "{letter}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A fixed size board running an [Isotropic] rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsotropicLife {
+    grid: Grid<bool>,
+    rule: Isotropic,
+}
+
+impl IsotropicLife {
+    /// Creates an empty board running Conway's Game of Life.
+    pub fn new(width: usize, height: usize) -> Self {
+        IsotropicLife {
+            grid: Grid::new(width, height),
+            rule: Isotropic::default(),
+        }
+    }
+
+    /// Sets the ruleset used when transitioning the board.
+    pub fn with_rule(self, rule: Isotropic) -> Self {
+        Self { rule, ..self }
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Isotropic {
+        self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self {
+            grid: self.grid.with_topology(topology),
+            ..self
+        }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.grid.topology()
+    }
+
+    /// Iterate over the cells of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.grid.iter()
+    }
+
+    /// The number of live cells on the board.
+    pub fn population(&self) -> usize {
+        self.grid.iter().filter(|c| *c).count()
+    }
+
+    /// Advance the board a single generation in place.
+    pub fn step(&mut self) {
+        let rule = self.rule;
+        self.grid
+            .step_with(|alive, neighbors| rule.is_alive(alive, &neighbors));
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Run the ruleset against the board and create the next iteration.
+    pub fn transition(&self) -> Self {
+        let mut next = self.clone();
+        next.step();
+        next
+    }
+}
+
+impl From<&GameOfLife> for IsotropicLife {
+    /// Copies the cells and topology of the board, running Conway's Game of Life until another
+    /// rule is set.
+    fn from(value: &GameOfLife) -> Self {
+        let width = value.width();
+        let mut board = IsotropicLife::new(width, value.height()).with_topology(value.topology());
+        for (i, alive) in value.iter().enumerate() {
+            if alive {
+                board
+                    .grid
+                    .write_cell(i % width, i / width, true)
+                    .expect("Cell is on the board");
+            }
+        }
+        board
+    }
+}
+
+impl From<&IsotropicLife> for GameOfLife {
+    /// Copies the cells and topology of the board. The rule isn't copied, since life-like rules
+    /// can't express most isotropic rules.
+    fn from(value: &IsotropicLife) -> Self {
+        let cells: Vec<bool> = value.iter().collect();
+        let rows: Vec<Vec<bool>> = cells
+            .chunks(value.grid.width().max(1))
+            .map(<[bool]>::to_vec)
+            .collect();
+        GameOfLife::from(rows).with_topology(value.topology())
+    }
+}
+
+impl Read for IsotropicLife {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        self.grid.read_cell(x, y)
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        self.grid.read_neighbors(x, y)
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        self.grid.read_neighborhood(x, y, neighborhood)
+    }
+
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+}
+
+impl Write for IsotropicLife {
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        self.grid.write_cell(x, y, alive)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{read::Read, utils::is_alive};
+use crate::{neighborhood::Neighborhood, read::Read, rule::Rule};
 
 pub trait IntoTransitionIter<I> {
     fn into_transition_iter(self) -> TransitionIter<I>;
@@ -8,6 +8,7 @@ impl<R: Read> IntoTransitionIter<R> for R {
     fn into_transition_iter(self) -> TransitionIter<R> {
         TransitionIter {
             inner: self,
+            rule: Rule::default(),
             x: 0,
             y: 0,
         }
@@ -16,9 +17,16 @@ impl<R: Read> IntoTransitionIter<R> for R {
 
 pub struct TransitionIter<R> {
     inner: R,
+    rule: Rule,
     x: usize,
     y: usize,
 }
+impl<R> TransitionIter<R> {
+    /// Sets the ruleset used to compute the next generation. Defaults to [Rule::CONWAY].
+    pub fn with_rule(self, rule: Rule) -> Self {
+        Self { rule, ..self }
+    }
+}
 impl<R: Read> Iterator for TransitionIter<R> {
     type Item = (usize, usize, bool);
 
@@ -28,8 +36,19 @@ impl<R: Read> Iterator for TransitionIter<R> {
         }
         let curr = (self.x, self.y);
         let cell = self.inner.read_cell(curr.0, curr.1).ok()?;
-        let neighbors = self.inner.read_neighbors(curr.0, curr.1).ok()?;
-        let new_value = is_alive(cell, &neighbors);
+        let new_value = match self.rule.neighborhood() {
+            Neighborhood::Moore(1) => {
+                let neighbors = self.inner.read_neighbors(curr.0, curr.1).ok()?;
+                self.rule.is_alive(cell, &neighbors)
+            }
+            neighborhood => {
+                let neighbors = self
+                    .inner
+                    .read_neighborhood(curr.0, curr.1, neighborhood)
+                    .ok()?;
+                self.rule.is_alive(cell, &neighbors)
+            }
+        };
 
         if self.x + 1 == self.inner.width() {
             self.x = 0;
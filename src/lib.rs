@@ -2,8 +2,28 @@
 //!
 //! This crate provides representations for the Game of Life and ways to run the game.
 
+pub mod active;
+pub mod bit_board;
+pub mod census;
+pub mod cycle;
+mod error;
 pub mod game_of_life;
+pub mod generations;
+pub mod grid;
+pub mod hashlife;
 pub mod io;
+pub mod isotropic;
 pub mod iter;
+pub mod ltl;
+pub mod margolus;
+pub mod neighborhood;
 pub mod read;
+pub mod reversible;
+pub mod rule;
+pub mod rule_table;
+pub mod sparse;
+pub mod topology;
 mod utils;
+pub mod write;
+
+pub use error::Error;
@@ -0,0 +1,510 @@
+//! # Larger than Life
+//! Larger than Life rules are totalistic rules over neighborhoods of a large radius, such as
+//! Bosco's Rule, `R5,C0,M1,S34..58,B34..45,NM`, which counts the 121 cells within a square of
+//! radius 5. They are written in HROT notation, with the counts given as ranges.
+//!
+//! Counting a neighborhood that size one cell at a time is far too slow, so [LtlLife] builds a
+//! summed-area table of the board each generation and reads every count off it with four
+//! lookups, whatever the radius. Von Neumann neighborhoods are diamonds rather than squares, so
+//! they are counted from running sums along each diagonal instead: moving a diamond one cell
+//! across or down gains two diagonal edges and loses two others, each read off with two
+//! lookups.
+
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use crate::{
+    error::Error,
+    game_of_life::GameOfLife,
+    neighborhood::Neighborhood,
+    read::Read,
+    rule::{self, Hrot},
+    topology::Topology,
+    write::Write,
+};
+
+/// The largest radius a Larger than Life rule can have.
+pub const MAX_RADIUS: u32 = 500;
+
+/// The shape of a Larger than Life neighborhood.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shape {
+    /// Every cell within a square of the radius around the cell.
+    #[default]
+    Moore,
+    /// Every cell within the radius of the cell, measured in orthogonal steps.
+    VonNeumann,
+}
+
+/// A Larger than Life ruleset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LargerThanLife {
+    radius: u32,
+    shape: Shape,
+    middle: bool,
+    birth: Vec<RangeInclusive<u32>>,
+    survival: Vec<RangeInclusive<u32>>,
+}
+
+impl LargerThanLife {
+    /// Creates a ruleset from its radius and neighborhood shape, whether a cell counts itself,
+    /// and the ranges of counts that cause a birth or let a cell survive. The radius must be
+    /// between 1 and [MAX_RADIUS].
+    pub fn new(
+        radius: u32,
+        shape: Shape,
+        middle: bool,
+        birth: Vec<RangeInclusive<u32>>,
+        survival: Vec<RangeInclusive<u32>>,
+    ) -> Result<Self, Error> {
+        if !(1..=MAX_RADIUS).contains(&radius) {
+            return Err(Error::InvalidRule(
+                "Larger than Life radius must be between 1 and 500",
+            ));
+        }
+        Ok(LargerThanLife {
+            radius,
+            shape,
+            middle,
+            birth,
+            survival,
+        })
+    }
+
+    /// Bosco's Rule, `R5,C0,M1,S34..58,B34..45,NM`.
+    pub fn bosco() -> Self {
+        Self::new(5, Shape::Moore, true, vec![34..=45], vec![34..=58])
+            .expect("Bosco's Rule is valid")
+    }
+
+    /// How far the neighborhood reaches from the cell in any direction.
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// The shape of the neighborhood.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// Whether a cell counts itself as one of its neighbors.
+    pub fn middle(&self) -> bool {
+        self.middle
+    }
+
+    /// The number of cells in the neighborhood, and so the highest count a cell can have.
+    pub fn size(&self) -> u32 {
+        let side = 2 * self.radius + 1;
+        let cells = match self.shape {
+            Shape::Moore => side * side,
+            Shape::VonNeumann => (side * side).div_ceil(2),
+        };
+        cells - u32::from(!self.middle)
+    }
+
+    /// Whether a dead cell with `count` live neighbors comes to life.
+    pub fn is_birth(&self, count: u32) -> bool {
+        self.birth.iter().any(|range| range.contains(&count))
+    }
+
+    /// Whether a live cell with `count` live neighbors survives.
+    pub fn is_survival(&self, count: u32) -> bool {
+        self.survival.iter().any(|range| range.contains(&count))
+    }
+
+    /// Returns the next state of a cell given its current state and number of live neighbors.
+    pub fn next_state(&self, current: bool, count: u32) -> bool {
+        if current {
+            self.is_survival(count)
+        } else {
+            self.is_birth(count)
+        }
+    }
+}
+
+impl Default for LargerThanLife {
+    /// Conway's Game of Life, `R1,C0,M0,S2..3,B3,NM`.
+    fn default() -> Self {
+        Self::new(1, Shape::Moore, false, vec![3..=3], vec![2..=3])
+            .expect("Conway's Game of Life is valid")
+    }
+}
+
+impl FromStr for LargerThanLife {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hrot: Hrot = s.parse()?;
+        if hrot.states > 2 {
+            return Err(Error::Unsupported(
+                "Larger than Life rules with more than two states",
+            ));
+        }
+        let shape = match hrot.shape {
+            rule::Shape::Moore => Shape::Moore,
+            rule::Shape::VonNeumann => Shape::VonNeumann,
+            _ => {
+                return Err(Error::Unsupported(
+                    "Larger than Life neighborhoods other than Moore and von Neumann",
+                ))
+            }
+        };
+        let ranges = |ranges: Vec<(u32, u32)>| ranges.into_iter().map(|(l, h)| l..=h).collect();
+        Self::new(
+            hrot.radius,
+            shape,
+            hrot.middle,
+            ranges(hrot.birth),
+            ranges(hrot.survival),
+        )
+    }
+}
+
+impl fmt::Display for LargerThanLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = |ranges: &[RangeInclusive<u32>]| {
+            ranges.iter().map(|r| (*r.start(), *r.end())).collect()
+        };
+        let hrot = Hrot {
+            radius: self.radius,
+            states: 2,
+            middle: self.middle,
+            survival: ranges(&self.survival),
+            birth: ranges(&self.birth),
+            shape: match self.shape {
+                Shape::Moore => rule::Shape::Moore,
+                Shape::VonNeumann => rule::Shape::VonNeumann,
+            },
+        };
+        write!(f, "{hrot}")
+    }
+}
+
+/// A fixed size board running a [LargerThanLife] rule.
+///
+/// Like [GameOfLife], the buffers used to compute the next generation are kept between steps, so
+/// [LtlLife::step] doesn't allocate once the board has been stepped once.
+#[derive(Debug, Clone)]
+pub struct LtlLife {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    scratch: Vec<bool>,
+    sums: SummedArea,
+    diagonals: DiagonalSums,
+    rule: LargerThanLife,
+    topology: Topology,
+}
+
+impl LtlLife {
+    /// Creates an empty board running Conway's Game of Life.
+    pub fn new(width: usize, height: usize) -> Self {
+        LtlLife {
+            width,
+            height,
+            cells: vec![false; width * height],
+            scratch: vec![false; width * height],
+            sums: SummedArea::default(),
+            diagonals: DiagonalSums::default(),
+            rule: LargerThanLife::default(),
+            topology: Topology::default(),
+        }
+    }
+
+    /// Sets the ruleset used when transitioning the board.
+    pub fn with_rule(self, rule: LargerThanLife) -> Self {
+        Self { rule, ..self }
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> &LargerThanLife {
+        &self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the cell at the coordinates provided, or None if they're off the board.
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Iterate over the cells of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// The number of live cells on the board.
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|c| **c).count()
+    }
+
+    /// Advance the board a single generation in place.
+    pub fn step(&mut self) {
+        let r = self.rule.radius as usize;
+        // The board with a margin of the radius around it, filled in according to the topology,
+        // so every neighborhood lies inside it.
+        let (width, height) = (self.width + 2 * r, self.height + 2 * r);
+        let padded = |x: usize, y: usize| {
+            let (x, y) = (x as isize - r as isize, y as isize - r as isize);
+            self.topology
+                .wrap(x, y, self.width, self.height)
+                .is_some_and(|(x, y)| self.cells[y * self.width + x])
+        };
+        let mut next = std::mem::take(&mut self.scratch);
+        let own = |i: usize| u32::from(!self.rule.middle && self.cells[i]);
+        match self.rule.shape {
+            Shape::Moore => {
+                self.sums.build(width, height, padded);
+                for (i, cell) in next.iter_mut().enumerate() {
+                    let (x, y) = (i % self.width + r, i / self.width + r);
+                    let count = self.sums.square(x, y, r) - own(i);
+                    *cell = self.rule.next_state(self.cells[i], count);
+                }
+            }
+            Shape::VonNeumann => {
+                self.diagonals.build(width, height, padded);
+                // Only the first diamond is counted cell by cell, every other one is moved from
+                // its neighbor to the left or above.
+                let r = r as isize;
+                let mut start = (-r..=r)
+                    .flat_map(|dy| (dy.abs() - r..=r - dy.abs()).map(move |dx| (r + dx, r + dy)))
+                    .filter(|(x, y)| padded(*x as usize, *y as usize))
+                    .count() as u32;
+                for y in 0..self.height {
+                    let mut count = start;
+                    for x in 0..self.width {
+                        let i = y * self.width + x;
+                        next[i] = self.rule.next_state(self.cells[i], count - own(i));
+                        if x + 1 < self.width {
+                            count = self
+                                .diagonals
+                                .right(count, x as isize + r, y as isize + r, r);
+                        }
+                    }
+                    if y + 1 < self.height {
+                        start = self.diagonals.down(start, r, y as isize + r, r);
+                    }
+                }
+            }
+        }
+        self.scratch = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Run the ruleset against the board and create the next iteration.
+    pub fn transition(&self) -> Self {
+        let mut next = self.clone();
+        next.step();
+        next
+    }
+}
+
+impl PartialEq for LtlLife {
+    /// Boards are equal when they have the same size, cells, rule and topology.
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.cells == other.cells
+            && self.rule == other.rule
+            && self.topology == other.topology
+    }
+}
+
+impl Eq for LtlLife {}
+
+impl From<&GameOfLife> for LtlLife {
+    /// Copies the cells and topology of the board, running Conway's Game of Life until another
+    /// rule is set.
+    fn from(value: &GameOfLife) -> Self {
+        let mut board = LtlLife::new(value.width(), value.height()).with_topology(value.topology());
+        board.cells = value.iter().collect();
+        board
+    }
+}
+
+impl From<&LtlLife> for GameOfLife {
+    /// Copies the cells and topology of the board. The rule isn't copied, since life-like rules
+    /// can't express most Larger than Life rules.
+    fn from(value: &LtlLife) -> Self {
+        let rows: Vec<Vec<bool>> = value
+            .cells
+            .chunks(value.width.max(1))
+            .map(<[bool]>::to_vec)
+            .collect();
+        GameOfLife::from(rows).with_topology(value.topology)
+    }
+}
+
+impl Read for LtlLife {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        self.get(x, y).ok_or(Error::OutOfBounds { x, y })
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self
+            .topology
+            .neighbor_coordinates(x, y, self.width, self.height)
+            .map(|coord| coord.map(|(x, y)| self.cells[y * self.width + x])))
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        Ok(self
+            .topology
+            .neighborhood_coordinates(x, y, self.width, self.height, neighborhood)
+            .map(|coord| coord.map(|(x, y)| self.cells[y * self.width + x]))
+            .collect())
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl Write for LtlLife {
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds { x, y });
+        }
+        self.cells[y * self.width + x] = alive;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A summed-area table, where each entry holds the number of live cells above and to the left of
+/// it, so the live cells in any rectangle can be counted with four lookups.
+#[derive(Debug, Clone, Default)]
+struct SummedArea {
+    /// One more than the width of the grid, since the first row and column are all zero.
+    stride: usize,
+    sums: Vec<u32>,
+}
+
+impl SummedArea {
+    /// Rebuilds the table over a grid of the given size, reusing its storage.
+    fn build(&mut self, width: usize, height: usize, alive: impl Fn(usize, usize) -> bool) {
+        self.stride = width + 1;
+        self.sums.clear();
+        self.sums.resize(self.stride * (height + 1), 0);
+        for y in 0..height {
+            let mut row = 0;
+            for x in 0..width {
+                row += u32::from(alive(x, y));
+                let i = (y + 1) * self.stride + x + 1;
+                self.sums[i] = self.sums[i - self.stride] + row;
+            }
+        }
+    }
+
+    /// The live cells within `r` of `(x, y)` in both directions, which must lie on the grid.
+    fn square(&self, x: usize, y: usize, r: usize) -> u32 {
+        let at = |x: usize, y: usize| self.sums[y * self.stride + x];
+        let (x0, y0, x1, y1) = (x - r, y - r, x + r + 1, y + r + 1);
+        at(x1, y1) + at(x0, y0) - at(x0, y1) - at(x1, y0)
+    }
+}
+
+/// Running sums along both diagonals of a grid, so the live cells on any stretch of a diagonal
+/// can be counted with two lookups.
+#[derive(Debug, Clone, Default)]
+struct DiagonalSums {
+    width: usize,
+    height: usize,
+    /// The live cells from each cell up and to the left, inclusive.
+    main: Vec<u32>,
+    /// The live cells from each cell up and to the right, inclusive.
+    anti: Vec<u32>,
+}
+
+impl DiagonalSums {
+    /// Rebuilds the sums over a grid of the given size, reusing their storage.
+    fn build(&mut self, width: usize, height: usize, alive: impl Fn(usize, usize) -> bool) {
+        (self.width, self.height) = (width, height);
+        self.main.clear();
+        self.main.resize(width * height, 0);
+        self.anti.clear();
+        self.anti.resize(width * height, 0);
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                let cell = u32::from(alive(x, y));
+                self.main[i] = cell
+                    + if x > 0 && y > 0 {
+                        self.main[i - width - 1]
+                    } else {
+                        0
+                    };
+                self.anti[i] = cell
+                    + if x + 1 < width && y > 0 {
+                        self.anti[i - width + 1]
+                    } else {
+                        0
+                    };
+            }
+        }
+    }
+
+    /// The entry of `sums` at `(x, y)`, or 0 off the grid.
+    fn at(&self, sums: &[u32], x: isize, y: isize) -> u32 {
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
+            return 0;
+        }
+        sums[y as usize * self.width + x as usize]
+    }
+
+    /// The live cells on the `len` cells down and to the right from `(x, y)`.
+    fn main_run(&self, x: isize, y: isize, len: isize) -> u32 {
+        self.at(&self.main, x + len - 1, y + len - 1) - self.at(&self.main, x - 1, y - 1)
+    }
+
+    /// The live cells on the `len` cells down and to the left from `(x, y)`.
+    fn anti_run(&self, x: isize, y: isize, len: isize) -> u32 {
+        self.at(&self.anti, x - len + 1, y + len - 1) - self.at(&self.anti, x + 1, y - 1)
+    }
+
+    /// Moves the count of a diamond of radius `r` around `(x, y)` one cell to the right.
+    fn right(&self, count: u32, x: isize, y: isize, r: isize) -> u32 {
+        let gained = self.main_run(x + 1, y - r, r + 1) + self.anti_run(x + r, y + 1, r);
+        let lost = self.anti_run(x, y - r, r + 1) + self.main_run(x - r + 1, y + 1, r);
+        count + gained - lost
+    }
+
+    /// Moves the count of a diamond of radius `r` around `(x, y)` one cell down.
+    fn down(&self, count: u32, x: isize, y: isize, r: isize) -> u32 {
+        let gained = self.main_run(x - r, y + 1, r + 1) + self.anti_run(x + r, y + 1, r);
+        let lost = self.anti_run(x, y - r, r + 1) + self.main_run(x + 1, y - r + 1, r);
+        count + gained - lost
+    }
+}
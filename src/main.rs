@@ -1,26 +1,10 @@
-use gol::{io::GoLFile, iter::IntoTransitionIter};
+use gol::io::transition_gol;
 
-fn main() -> Result<(), &'static str> {
+fn main() -> Result<(), gol::Error> {
     let start = std::time::Instant::now();
-    // {
-    //     let file = std::fs::File::open("./big.gol").unwrap();
-    //     let data = read_gol_file_good(file)?;
-    //     let result = transition(&data);
-    //     println!("{}", start.elapsed().as_millis());
-    // }
-
-    // let mut file = std::fs::File::open("./medium.gol").or(Err("Failed to open test file"))?;
-    // let mut file = std::fs::File::open("./reader_test.gol").or(Err("Failed to open test file"))?;
-
-    let file = std::fs::File::open("./big.gol").or(Err("Failed to open test file"))?;
-    let reader = GoLFile::with_max_capacity(file, 300000)?;
-    let mut output = Vec::with_capacity(reader.height);
-    for _ in 0..reader.height {
-        output.push(vec![false; reader.width]);
-    }
-    reader.into_transition_iter().for_each(|v| {
-        output[v.0][v.1] = v.2;
-    });
+    let input = std::fs::File::open("./big.gol")?;
+    let output = std::fs::File::create("./big.next.gol")?;
+    transition_gol(input, output)?;
     println!("{}", start.elapsed().as_millis());
 
     Ok(())
@@ -28,8 +12,35 @@ fn main() -> Result<(), &'static str> {
 
 #[cfg(test)]
 mod tests {
+    use gol::game_of_life::GameOfLife;
     use gol::io::{read_gol, write_gol};
 
+    /// Builds a board from rows of `.` (dead) and `O` (alive) cells.
+    fn board(rows: &[&str]) -> GameOfLife {
+        rows.iter()
+            .map(|row| row.chars().map(|c| c == 'O').collect())
+            .collect::<Vec<Vec<bool>>>()
+            .into()
+    }
+
+    /// Builds a pseudo-random board from a seed, roughly a third of the cells alive.
+    fn soup(width: usize, height: usize, seed: u64) -> GameOfLife {
+        let mut state = seed.wrapping_mul(6364136223846793005) | 1;
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        state.is_multiple_of(3)
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<bool>>>()
+            .into()
+    }
+
     #[test]
     fn write_and_read_return_same_data() {
         let data = vec![
@@ -44,13 +55,14 @@ mod tests {
         assert_eq!(data, data2);
     }
 
+    #[allow(clippy::bool_assert_comparison)]
     mod reader {
         use gol::io::GoLFile;
         use gol::read::Read;
         use std::io::Cursor;
 
         #[test]
-        fn works_with_files_smaller_than_buffer() -> Result<(), &'static str> {
+        fn works_with_files_smaller_than_buffer() -> Result<(), gol::Error> {
             let data = b"GOFL\x00\x00\x00\x01\x00\x00\x00\x01\x00";
             let mut reader = GoLFile::new(Cursor::new(data))?;
             assert_eq!(reader.width, 1);
@@ -74,7 +86,7 @@ mod tests {
         }
 
         #[test]
-        fn works_with_files_larger_than_buffer() -> Result<(), &'static str> {
+        fn works_with_files_larger_than_buffer() -> Result<(), gol::Error> {
             let data = b"GOFL\x00\x00\x00\x0b\x00\x00\x00\x06\x00\x00\x00\x00\x00\
                 \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
                 \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
@@ -89,4 +101,1888 @@ mod tests {
             Ok(())
         }
     }
+
+    mod rule {
+        use gol::game_of_life::GameOfLife;
+        use gol::rule::Rule;
+
+        #[test]
+        fn parses_and_prints_rulestrings() -> Result<(), gol::Error> {
+            assert_eq!("B3/S23".parse::<Rule>()?, Rule::CONWAY);
+            assert_eq!("b36/s23".parse::<Rule>()?, Rule::HIGHLIFE);
+            assert_eq!("S34678/B3678".parse::<Rule>()?, Rule::DAY_AND_NIGHT);
+            assert_eq!("/2".parse::<Rule>()?, Rule::SEEDS);
+            assert_eq!(Rule::new(&[3, 6], &[2, 3])?, Rule::HIGHLIFE);
+            assert_eq!(Rule::HIGHLIFE.to_string(), "B36/S23");
+            assert_eq!(Rule::SEEDS.to_string(), "B2/S");
+            assert!("B9/S23".parse::<Rule>().is_err());
+            assert!("B3S23".parse::<Rule>().is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn transition_uses_the_board_rule() {
+            let board: GameOfLife = vec![
+                vec![false, false, false, false],
+                vec![false, true, true, false],
+                vec![false, false, false, false],
+            ]
+            .into();
+            assert_eq!(board.transition().iter().filter(|c| *c).count(), 0);
+            let seeds = board.with_rule(Rule::SEEDS).transition();
+            let expected: GameOfLife = vec![
+                vec![false, true, true, false],
+                vec![false, false, false, false],
+                vec![false, true, true, false],
+            ]
+            .into();
+            assert_eq!(seeds, expected.with_rule(Rule::SEEDS));
+        }
+    }
+
+    mod topology {
+        use gol::io::{write_gol, GoLFile};
+        use gol::iter::IntoTransitionIter;
+        use gol::topology::Topology;
+        use std::io::Cursor;
+
+        #[test]
+        fn wraps_coordinates_across_edges() {
+            assert_eq!(Topology::Bounded.wrap(-1, 0, 4, 3), None);
+            assert_eq!(Topology::Torus.wrap(-1, 3, 4, 3), Some((3, 0)));
+            assert_eq!(Topology::KleinBottle.wrap(1, -1, 4, 3), Some((2, 2)));
+            assert_eq!(Topology::KleinBottle.wrap(4, 1, 4, 3), Some((0, 1)));
+            assert_eq!(Topology::CrossSurface.wrap(-1, 0, 4, 3), Some((3, 2)));
+            assert_eq!(Topology::CrossSurface.wrap(0, 3, 4, 3), Some((3, 0)));
+        }
+
+        #[test]
+        fn glider_wraps_around_a_torus() {
+            let start = super::board(&[".O....", "..O...", "OOO...", "......", "......", "......"])
+                .with_topology(Topology::Torus);
+            let mut board = start.transition();
+            for _ in 1..24 {
+                board = board.transition();
+            }
+            assert_eq!(board, start);
+
+            let mut bounded =
+                super::board(&[".O....", "..O...", "OOO...", "......", "......", "......"]);
+            for _ in 0..24 {
+                bounded = bounded.transition();
+            }
+            assert_eq!(bounded.iter().filter(|c| *c).count(), 4);
+        }
+
+        #[test]
+        fn file_transition_matches_in_memory_transition() -> Result<(), gol::Error> {
+            let board = super::board(&["O...O", "....O", ".....", "O....", "OO..O"]);
+            for topology in [
+                Topology::Torus,
+                Topology::KleinBottle,
+                Topology::CrossSurface,
+            ] {
+                let board = board.clone().with_topology(topology);
+                let mut data = Vec::new();
+                write_gol(&mut data, &board)?;
+                let reader = GoLFile::new(Cursor::new(data))?.with_topology(topology);
+                let cells: Vec<bool> = reader.into_transition_iter().map(|c| c.2).collect();
+                assert_eq!(cells, board.transition().iter().collect::<Vec<_>>());
+            }
+            Ok(())
+        }
+    }
+
+    mod bit_board {
+        use gol::bit_board::BitBoard;
+        use gol::game_of_life::GameOfLife;
+        use gol::read::Read;
+        use gol::rule::Rule;
+        use gol::topology::Topology;
+
+        #[test]
+        fn transition_matches_game_of_life() {
+            for (width, height) in [(1, 1), (5, 3), (64, 4), (70, 9), (130, 7)] {
+                for topology in [
+                    Topology::Bounded,
+                    Topology::Torus,
+                    Topology::KleinBottle,
+                    Topology::CrossSurface,
+                ] {
+                    for rule in [Rule::CONWAY, Rule::HIGHLIFE, Rule::DAY_AND_NIGHT] {
+                        let mut board = super::soup(width, height, (width * height) as u64)
+                            .with_rule(rule)
+                            .with_topology(topology);
+                        let mut bits = BitBoard::from(&board);
+                        for _ in 0..4 {
+                            board = board.transition();
+                            bits = bits.transition();
+                            assert_eq!(GameOfLife::from(&bits), board);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn reads_cells_and_neighbors() -> Result<(), gol::Error> {
+            let mut bits = BitBoard::from(&super::board(&["O.O", "...", ".OO"]));
+            assert_eq!(bits.width(), 3);
+            assert!(bits.read_cell(2, 0)?);
+            assert!(bits.read_cell(3, 0).is_err());
+            assert_eq!(
+                bits.read_neighbors(0, 1)?,
+                [
+                    None,
+                    Some(true),
+                    Some(false),
+                    Some(false),
+                    Some(true),
+                    Some(false),
+                    None,
+                    None
+                ]
+            );
+            assert!(matches!(
+                bits.read_neighbors(1, 3),
+                Err(gol::Error::OutOfBounds { x: 1, y: 3 })
+            ));
+            assert_eq!(bits.population(), 4);
+            Ok(())
+        }
+    }
+
+    mod hashlife {
+        use gol::game_of_life::GameOfLife;
+        use gol::hashlife::HashLife;
+        use gol::rule::Rule;
+
+        #[test]
+        fn glider_travels_in_one_big_step() -> Result<(), gol::Error> {
+            let glider = super::board(&[".O.", "..O", "OOO"]);
+            let mut universe = HashLife::try_from(&glider)?;
+            universe.step(10)?;
+            assert_eq!(universe.generation(), 1024);
+            assert_eq!(universe.population(), 5);
+            assert_eq!(universe.bounding_box(), Some((256, 256, 258, 258)));
+            assert_eq!(GameOfLife::from(&universe), glider);
+            assert!(matches!(
+                universe.step(255),
+                Err(gol::Error::Unsupported(_))
+            ));
+            assert_eq!(universe.generation(), 1024);
+            Ok(())
+        }
+
+        #[test]
+        fn matches_game_of_life() -> Result<(), gol::Error> {
+            let soup = super::soup(16, 16, 7);
+            let mut board = vec![vec![false; 100]; 100];
+            for (i, alive) in soup.iter().enumerate() {
+                board[42 + i / 16][42 + i % 16] = alive;
+            }
+            for rule in [Rule::CONWAY, Rule::HIGHLIFE] {
+                let mut board = GameOfLife::from(board.clone()).with_rule(rule);
+                let mut universe = HashLife::try_from(&board)?.with_memory_limit(0);
+                for _ in 0..37 {
+                    board = board.transition();
+                }
+                universe.advance_by(37)?;
+                assert_eq!(universe.window(0, 0, 100, 100), board);
+                assert!(universe.get_cell(-1, -1) == board.iter().next().unwrap());
+            }
+            assert!(HashLife::new().with_rule(Rule::SEEDS).is_ok());
+            assert!(HashLife::new().with_rule("B03/S23".parse()?).is_err());
+            Ok(())
+        }
+    }
+
+    mod sparse {
+        use gol::game_of_life::GameOfLife;
+        use gol::rule::Rule;
+        use gol::sparse::SparseLife;
+
+        #[test]
+        fn spaceships_keep_travelling() -> Result<(), gol::Error> {
+            let glider = super::board(&["...", ".O.", "..O", "OOO", "..."]);
+            let mut sparse = SparseLife::try_from(&glider)?;
+            assert_eq!(sparse.bounding_box(), Some((0, 1, 2, 3)));
+            for _ in 0..400 {
+                sparse = sparse.transition();
+            }
+            assert_eq!(sparse.population(), 5);
+            assert_eq!(sparse.bounding_box(), Some((100, 101, 102, 103)));
+            assert_eq!(
+                GameOfLife::from(&sparse),
+                super::board(&[".O.", "..O", "OOO"])
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn matches_game_of_life_inside_window() -> Result<(), gol::Error> {
+            let board = super::soup(30, 30, 3).with_rule(Rule::HIGHLIFE);
+            let mut sparse = SparseLife::try_from(&board)?;
+            let mut wide = sparse.window(-20, -20, 70, 70);
+            for _ in 0..10 {
+                sparse = sparse.transition();
+                wide = wide.transition();
+            }
+            assert_eq!(sparse.window(-20, -20, 70, 70), wide);
+            assert!(SparseLife::new().with_rule("B0/S".parse()?).is_err());
+            Ok(())
+        }
+    }
+
+    mod rle {
+        use gol::io::rle::{read_rle_with_comments, write_rle_with_comments};
+        use gol::io::{read_rle, write_rle, Comments};
+        use gol::rule::Rule;
+        use gol::topology::Topology;
+
+        #[test]
+        fn reads_header_comments_and_runs() -> Result<(), gol::Error> {
+            let data = "#N Glider pair\n#O Someone\n#C Two gliders\n#C split by rows\n\
+                x = 4, y = 7, rule = B36/S23:T4,7\n\
+                bo$2bo$3o3$\n2o$2o!";
+            let (board, comments) = read_rle_with_comments(data.as_bytes())?;
+            assert_eq!(comments.name.as_deref(), Some("Glider pair"));
+            assert_eq!(comments.author.as_deref(), Some("Someone"));
+            assert_eq!(comments.comments, ["Two gliders", "split by rows"]);
+            assert_eq!(board.rule(), Rule::HIGHLIFE);
+            assert_eq!(board.topology(), Topology::Torus);
+            let expected = super::board(&[".O..", "..O.", "OOO.", "....", "....", "OO..", "OO.."]);
+            assert_eq!(
+                board,
+                expected
+                    .with_rule(Rule::HIGHLIFE)
+                    .with_topology(Topology::Torus)
+            );
+            assert!(read_rle("x = 2, y = 1\n3o!".as_bytes()).is_err());
+            assert!(matches!(
+                read_rle("x = 4000000000, y = 4000000000\no!".as_bytes()),
+                Err(gol::Error::Unsupported(_))
+            ));
+            for body in ["$18446744073709551615$!", "2b18446744073709551615b!"] {
+                assert!(matches!(
+                    read_rle(format!("x = 3, y = 3\n{body}").as_bytes()),
+                    Err(gol::Error::Malformed(_))
+                ));
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn round_trips_through_game_of_life() -> Result<(), gol::Error> {
+            let board = super::soup(150, 40, 11).with_rule(Rule::DAY_AND_NIGHT);
+            let comments = Comments {
+                name: Some("Soup".into()),
+                author: None,
+                comments: vec!["A random soup".into()],
+            };
+            let mut data = Vec::new();
+            write_rle_with_comments(&mut data, &board, &comments)?;
+            let text = String::from_utf8(data.clone()).unwrap();
+            assert!(text.lines().all(|line| line.len() <= 70));
+            assert_eq!(read_rle_with_comments(data.as_slice())?, (board, comments));
+
+            let sparse = super::board(&["....", "O...", "....", "....", "...."]);
+            let mut data = Vec::new();
+            write_rle(&mut data, &sparse)?;
+            assert_eq!(data, b"x = 4, y = 5, rule = B3/S23\n$o!\n");
+            assert_eq!(read_rle(data.as_slice())?, sparse);
+            Ok(())
+        }
+    }
+
+    mod formats {
+        use gol::io::life105::write_life105_with_comments;
+        use gol::io::plaintext::read_cells_with_comments;
+        use gol::io::{
+            read_cells, read_life105, read_life106, read_pattern, write_cells, write_gol,
+            write_life106, write_rle, Comments,
+        };
+        use gol::rule::Rule;
+
+        #[test]
+        fn reads_plaintext_with_comments() -> Result<(), gol::Error> {
+            let data = "!Name: Glider\n!The smallest spaceship\n.O\n..O\nOOO\n";
+            let (board, comments) = read_cells_with_comments(data.as_bytes())?;
+            assert_eq!(board, super::board(&[".O.", "..O", "OOO"]));
+            assert_eq!(comments.name.as_deref(), Some("Glider"));
+            assert_eq!(comments.comments, ["The smallest spaceship"]);
+
+            let mut data = Vec::new();
+            write_cells(&mut data, &board)?;
+            assert_eq!(data, b".O.\n..O\nOOO\n");
+            assert_eq!(read_cells(data.as_slice())?, board);
+            Ok(())
+        }
+
+        #[test]
+        fn reads_life_1_0x_cropped_to_live_cells() -> Result<(), gol::Error> {
+            let data = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+            assert_eq!(
+                read_life106(data.as_bytes())?,
+                super::board(&[".O.", "..O", "OOO"])
+            );
+
+            let data = "#Life 1.05\n#D Two blocks\n#R 23/36\n#P -3 -1\n**\n**\n#P 2 2\n.**\n.**\n";
+            let board = read_life105(data.as_bytes())?;
+            assert_eq!(board.rule(), Rule::HIGHLIFE);
+            let expected =
+                super::board(&["OO......", "OO......", "........", "......OO", "......OO"]);
+            assert_eq!(board, expected.with_rule(Rule::HIGHLIFE));
+
+            let far_apart = format!("#Life 1.06\n{} 0\n{} 0\n", i64::MIN, i64::MAX);
+            assert!(matches!(
+                read_life106(far_apart.as_bytes()),
+                Err(gol::Error::Unsupported(_))
+            ));
+            assert!(matches!(
+                read_life106("#Life 1.06\n0 0\n100000000 100000000\n".as_bytes()),
+                Err(gol::Error::Unsupported(_))
+            ));
+            let past_the_edge = format!("#Life 1.05\n#P {} 0\n.**\n", i64::MAX);
+            assert!(matches!(
+                read_life105(past_the_edge.as_bytes()),
+                Err(gol::Error::Malformed(_))
+            ));
+
+            let board = super::soup(20, 12, 5).with_rule(Rule::DAY_AND_NIGHT);
+            let comments = Comments {
+                comments: vec!["A soup".into()],
+                ..Comments::default()
+            };
+            let mut data = Vec::new();
+            write_life105_with_comments(&mut data, &board, &comments)?;
+            assert!(String::from_utf8_lossy(&data).contains("#R 34678/3678\n"));
+            let read = read_life105(data.as_slice())?;
+            assert_eq!(read.rule(), Rule::DAY_AND_NIGHT);
+            assert_eq!(
+                read.iter().filter(|c| *c).count(),
+                board.iter().filter(|c| *c).count()
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn detects_formats() -> Result<(), gol::Error> {
+            let board = super::board(&[".O.", "..O", "OOO"]);
+            let mut gol = Vec::new();
+            write_gol(&mut gol, &board)?;
+            let mut rle = b"#N Glider\n".to_vec();
+            write_rle(&mut rle, &board)?;
+            let mut life106 = Vec::new();
+            write_life106(&mut life106, &board)?;
+            let mut cells = b"!Name: Glider\n".to_vec();
+            write_cells(&mut cells, &board)?;
+            for data in [gol, rle, life106, cells] {
+                assert_eq!(read_pattern(data.as_slice())?, board);
+            }
+            assert_eq!(
+                read_pattern(&b"x = 3, y = 1\n3o!"[..])?,
+                super::board(&["OOO"])
+            );
+            assert!(read_pattern(&b"GIF89a"[..]).is_err());
+            Ok(())
+        }
+    }
+
+    mod macrocell {
+        use gol::game_of_life::GameOfLife;
+        use gol::hashlife::HashLife;
+        use gol::io::{read_mc, read_pattern, write_mc};
+        use gol::rule::Rule;
+        use gol::sparse::SparseLife;
+
+        #[test]
+        fn reads_golly_macrocells() -> Result<(), gol::Error> {
+            let data = "[M2] (golly 4.0)\n#R B36/S23\n#G 12\n$$..*$...*$.***$\n4 0 0 0 1\n";
+            let universe = read_mc(data.as_bytes())?;
+            assert_eq!(universe.rule(), Rule::HIGHLIFE);
+            assert_eq!(universe.generation(), 12);
+            assert_eq!(universe.population(), 5);
+            assert_eq!(universe.bounding_box(), Some((1, 2, 3, 4)));
+            assert!(read_mc(&b"[M2]\n4 0 0 0 2\n"[..]).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn round_trips_huge_sparse_patterns() -> Result<(), gol::Error> {
+            let mut sparse = SparseLife::new();
+            let far = 1 << 40;
+            for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1), (far, -far), (-far, far + 1)] {
+                sparse.set(x, y, true);
+            }
+            let universe = HashLife::try_from(&sparse)?;
+            let mut data = Vec::new();
+            write_mc(&mut data, &universe)?;
+            assert!(data.len() < 4096);
+            let read = read_mc(data.as_slice())?;
+            assert_eq!(read.bounding_box(), universe.bounding_box());
+            assert_eq!(SparseLife::from(&read), sparse);
+
+            let board = super::soup(40, 25, 9);
+            let mut data = Vec::new();
+            write_mc(&mut data, &HashLife::try_from(&board)?)?;
+            let read = read_pattern(data.as_slice())?;
+            let sparse = SparseLife::try_from(&board)?;
+            assert_eq!(read, GameOfLife::from(&sparse));
+            Ok(())
+        }
+
+        #[test]
+        fn refuses_boards_too_large_to_allocate() {
+            // Two cells at opposite corners of a level 41 node, 2^41 cells apart.
+            let mut data = String::from("[M2]\n*$\n$$$$$$$.......*$\n");
+            let (mut top_left, mut bottom_right) = (1, 2);
+            for level in 4..41 {
+                data.push_str(&format!("{level} {top_left} 0 0 0\n"));
+                data.push_str(&format!("{level} 0 0 0 {bottom_right}\n"));
+                (top_left, bottom_right) = (bottom_right + 1, bottom_right + 2);
+            }
+            data.push_str(&format!("41 {top_left} 0 0 {bottom_right}\n"));
+            assert!(read_mc(data.as_bytes()).is_ok());
+            assert!(matches!(
+                read_pattern(data.as_bytes()),
+                Err(gol::Error::Unsupported(_))
+            ));
+        }
+    }
+
+    mod gol_v2 {
+        use gol::io::{read_gol, write_gol_with_version, GoLFile, Version};
+        use gol::iter::IntoTransitionIter;
+        use gol::read::Read;
+        use std::io::Cursor;
+
+        #[test]
+        fn packs_cells_into_bits() -> Result<(), gol::Error> {
+            let board = super::board(&["O........O", "..........", ".OO......."]);
+            let mut data = Vec::new();
+            write_gol_with_version(&mut data, &board, Version::V2)?;
+            assert_eq!(
+                data,
+                b"GOFV\x02\x01\x00\x00\x00\x00\x00\x0a\x00\x00\x00\x03\
+                    \x80\x40\x00\x00\x60\x00"
+            );
+            assert_eq!(read_gol(data.as_slice())?, board);
+            assert!(
+                read_gol(&b"GOFV\x03\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00\x01\x00"[..]).is_err()
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn reads_v2_files_randomly() -> Result<(), gol::Error> {
+            let board = super::soup(37, 29, 21);
+            let mut data = Vec::new();
+            write_gol_with_version(&mut data, &board, Version::V2)?;
+            assert_eq!(data.len(), 16 + 5 * 29);
+
+            let mut reader = GoLFile::with_max_capacity(Cursor::new(data.clone()), 40)?;
+            assert_eq!(reader.header().version, Version::V2);
+            let cells: Vec<bool> = board.iter().collect();
+            for (x, y) in [(36, 28), (0, 0), (17, 3), (8, 20), (35, 1)] {
+                assert_eq!(reader.read_cell(x, y)?, cells[y * 37 + x]);
+            }
+            let reader = GoLFile::with_max_capacity(Cursor::new(data), 100)?;
+            let next: Vec<bool> = reader.into_transition_iter().map(|c| c.2).collect();
+            assert_eq!(next, board.transition().iter().collect::<Vec<_>>());
+            Ok(())
+        }
+    }
+
+    mod metadata {
+        use gol::io::{
+            read_gol, read_gol_with_metadata, write_gol_with_metadata, GoLFile, Metadata,
+        };
+        use gol::read::Read;
+        use gol::rule::Rule;
+        use std::io::Cursor;
+
+        #[test]
+        fn round_trips_metadata() -> Result<(), gol::Error> {
+            let board = super::soup(12, 7, 4).with_rule(Rule::HIGHLIFE);
+            let mut metadata = Metadata {
+                rule: Some(Rule::HIGHLIFE),
+                generation: Some(1234),
+                author: Some("Someone".into()),
+                name: Some("Soup".into()),
+                origin: Some((-40, 17)),
+                ..Metadata::default()
+            };
+            metadata.other.insert("seed".into(), "4".into());
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &board, &metadata)?;
+            assert_eq!(
+                read_gol_with_metadata(data.as_slice())?,
+                (board.clone(), metadata.clone())
+            );
+            assert_eq!(read_gol(data.as_slice())?.rule(), Rule::HIGHLIFE);
+
+            let mut reader = GoLFile::new(Cursor::new(data))?;
+            assert_eq!(reader.metadata(), &metadata);
+            assert!(reader.header().has_metadata());
+            let cells: Vec<bool> = board.iter().collect();
+            for (x, y) in [(11, 6), (0, 0), (5, 3)] {
+                assert_eq!(reader.read_cell(x, y)?, cells[y * 12 + x]);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn writes_each_key_once() -> Result<(), gol::Error> {
+            let board = super::soup(5, 5, 2);
+            let mut metadata = Metadata {
+                rule: Some(Rule::HIGHLIFE),
+                ..Metadata::default()
+            };
+            // An unparseable rule kept from an earlier read, now replaced by a typed one.
+            metadata.other.insert("rule".into(), "B3/S23/Hex".into());
+            metadata.other.insert("seed".into(), "2".into());
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &board, &metadata)?;
+            assert_eq!(data.windows(4).filter(|w| w == b"rule").count(), 1);
+            let (_, read) = read_gol_with_metadata(data.as_slice())?;
+            assert_eq!(read.rule, Some(Rule::HIGHLIFE));
+            assert_eq!(read.other.get("rule"), None);
+            assert_eq!(read.other.get("seed").map(String::as_str), Some("2"));
+            Ok(())
+        }
+
+        #[test]
+        fn skips_metadata_by_length() -> Result<(), gol::Error> {
+            let mut data = b"GOFV\x02\x11\x00\x01\x00\x00\x00\x03\x00\x00\x00\x01".to_vec();
+            let mut extension = b"\x00\x00\x00\x0a\x04seed\x00\x00\x00\x01\x39".to_vec();
+            extension.resize(256, 0);
+            data.extend(extension);
+            data.push(0xa0);
+            let (board, metadata) = read_gol_with_metadata(&data[..])?;
+            assert_eq!(board, super::board(&["O.O"]));
+            assert_eq!(metadata.other.get("seed").map(String::as_str), Some("9"));
+            assert_eq!(metadata.rule, None);
+            Ok(())
+        }
+
+        #[test]
+        fn ignores_unknown_extension_flags() -> Result<(), gol::Error> {
+            let board = super::soup(9, 4, 3);
+            let metadata = Metadata {
+                generation: Some(12),
+                ..Metadata::default()
+            };
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &board, &metadata)?;
+            // To a reader from before metadata, the metadata flag is just an unknown flag that
+            // only describes the extension.
+            data[5] = data[5] & !0x10 | 0x80;
+            let (read, metadata) = read_gol_with_metadata(&data[..])?;
+            assert_eq!(read, board);
+            assert_eq!(metadata, Metadata::default());
+            assert_eq!(read_gol(&data[..])?, board);
+            Ok(())
+        }
+    }
+
+    mod errors {
+        use std::error::Error as _;
+        use std::io::Cursor;
+
+        use gol::io::{
+            read_gol, read_gol_generations, read_gol_reversible, write_gol_iterator, GoLFile,
+        };
+        use gol::read::Read;
+        use gol::Error;
+
+        #[test]
+        fn reports_what_went_wrong() {
+            assert!(matches!(read_gol(&b"GIF89a..."[..]), Err(Error::BadMagic)));
+            assert!(matches!(
+                read_gol(&b"GOFL\x00\x00"[..]),
+                Err(Error::TruncatedHeader)
+            ));
+            assert!(matches!(
+                read_gol(&b"GOFL\x00\x00\x00\x02\x00\x00\x00\x02\x01"[..]),
+                Err(Error::TruncatedBody {
+                    expected: 4,
+                    got: 1
+                })
+            ));
+            assert!(matches!(
+                GoLFile::new(Cursor::new(b"GOFL\x00\x00\x00\x02\x00\x00\x00\x02\x01")),
+                Err(Error::TruncatedBody {
+                    expected: 4,
+                    got: 1
+                })
+            ));
+            // A header claiming the largest possible board, with no body at all.
+            let huge = b"GOFL\xff\xff\xff\xff\xff\xff\xff\xff";
+            assert!(matches!(
+                read_gol(&huge[..]),
+                Err(Error::TruncatedBody { got: 0, .. })
+            ));
+            assert!(matches!(
+                read_gol_generations(&huge[..]),
+                Err(Error::TruncatedBody { got: 0, .. })
+            ));
+            assert!(matches!(
+                read_gol_reversible(&huge[..]),
+                Err(Error::TruncatedBody { got: 0, .. })
+            ));
+            assert!(matches!(
+                read_gol(&b"GOFV\x03\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x01"[..]),
+                Err(Error::UnsupportedVersion(3))
+            ));
+            // An extension claiming to be nearly 64KiB long, cut short.
+            assert!(matches!(
+                read_gol(
+                    &b"GOFV\x02\x11\x00\xff\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00\x05\x04seed"[..]
+                ),
+                Err(Error::Malformed(_))
+            ));
+            // A metadata block claiming to be longer than its extension.
+            let mut data =
+                b"GOFV\x02\x11\x00\x01\x00\x00\x00\x01\x00\x00\x00\x01\xff\xff\xff\xf0".to_vec();
+            data.resize(16 + 256 + 1, 0);
+            assert!(matches!(read_gol(&data[..]), Err(Error::Malformed(_))));
+            assert!(matches!(
+                "B3/S29".parse::<gol::rule::Rule>(),
+                Err(Error::InvalidRule(_))
+            ));
+        }
+
+        #[test]
+        fn rejects_cells_off_the_board() -> Result<(), Error> {
+            let mut reader =
+                GoLFile::new(Cursor::new(b"GOFL\x00\x00\x00\x01\x00\x00\x00\x01\x01"))?;
+            assert!(matches!(
+                reader.read_cell(1, 0),
+                Err(Error::OutOfBounds { x: 1, y: 0 })
+            ));
+            let mut board = super::board(&["O"]);
+            assert!(matches!(
+                board.read_cell(0, 2),
+                Err(Error::OutOfBounds { x: 0, y: 2 })
+            ));
+            Ok(())
+        }
+
+        #[test]
+        fn rejects_the_wrong_number_of_cells() {
+            let mut output = Vec::new();
+            let result = write_gol_iterator(&mut output, 2, 2, [true; 3].into_iter());
+            assert!(matches!(result, Err(Error::DimensionMismatch)));
+            let result = write_gol_iterator(&mut output, 2, 2, [true; 5].into_iter());
+            assert!(matches!(result, Err(Error::DimensionMismatch)));
+        }
+
+        #[test]
+        fn keeps_the_underlying_io_error() {
+            let err = Error::from(std::io::Error::other("disk on fire"));
+            assert_eq!(
+                err.source().map(ToString::to_string).as_deref(),
+                Some("disk on fire")
+            );
+            assert!(Error::BadMagic.source().is_none());
+        }
+    }
+
+    mod write {
+        use std::io::Cursor;
+
+        use gol::io::{read_gol, write_gol, write_gol_with_version, GoLFile, Version};
+        use gol::read::Read;
+        use gol::write::Write;
+        use gol::Error;
+
+        #[test]
+        fn patches_files_in_place() -> Result<(), Error> {
+            for version in [Version::V1, Version::V2] {
+                let mut expected = super::soup(13, 9, 4);
+                let mut data = Vec::new();
+                write_gol_with_version(&mut data, &expected, version)?;
+                // A small buffer, so writes land both inside and outside of it.
+                let mut file = GoLFile::with_max_capacity(Cursor::new(data), 15)?;
+
+                file.read_cell(6, 4)?;
+                for board in [&mut file as &mut dyn Write, &mut expected] {
+                    board.write_cell(6, 4, true)?;
+                    board.write_cell(12, 8, true)?;
+                    board.write_row(3, 0, &[true, false, true, true, false, true])?;
+                    board.fill_rect(1, 5, 11, 3, false)?;
+                }
+                assert!(file.read_cell(6, 4)?);
+                assert!(file.read_cell(12, 8)?);
+                assert!(!file.read_cell(11, 7)?);
+                file.flush()?;
+
+                let written = file.into_inner().into_inner();
+                assert_eq!(read_gol(&written[..])?, expected);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn rejects_writes_off_the_board() -> Result<(), Error> {
+            let mut data = Vec::new();
+            write_gol(&mut data, &super::board(&["...", "..."]))?;
+            let mut file = GoLFile::new(Cursor::new(data))?;
+            assert!(matches!(
+                file.write_cell(3, 0, true),
+                Err(Error::OutOfBounds { x: 3, y: 0 })
+            ));
+            assert!(file.write_row(1, 1, &[true; 3]).is_err());
+            assert!(file.fill_rect(0, 0, 3, 3, true).is_err());
+            assert!(matches!(
+                file.write_row(usize::MAX, 0, &[true; 2]),
+                Err(Error::OutOfBounds { .. })
+            ));
+            assert!(matches!(
+                file.fill_rect(1, usize::MAX, 1, 2, true),
+                Err(Error::OutOfBounds { .. })
+            ));
+            file.flush()?;
+            assert!(!file.read_cell(1, 1)?);
+            Ok(())
+        }
+    }
+
+    mod stepping {
+        use gol::topology::Topology;
+
+        #[test]
+        fn steps_in_place_like_transition() {
+            let board = super::soup(23, 19, 8).with_topology(Topology::Torus);
+            let mut stepped = board.clone();
+            stepped.step();
+            assert_eq!(stepped, board.transition());
+
+            let mut expected = board.clone();
+            for _ in 0..10 {
+                expected = expected.transition();
+            }
+            stepped.step_n(9);
+            assert_eq!(stepped, expected);
+        }
+
+        #[test]
+        fn compares_only_the_current_generation() {
+            let blinker = super::board(&[".....", "..O..", "..O..", "..O..", "....."]);
+            let mut stepped = blinker.clone();
+            stepped.step_n(2);
+            // The spare buffer now holds the other phase, which mustn't affect equality.
+            assert_eq!(stepped, blinker);
+            stepped.step();
+            assert_ne!(stepped, blinker);
+        }
+    }
+
+    mod active {
+        use gol::active::ActiveLife;
+        use gol::game_of_life::GameOfLife;
+        use gol::topology::Topology;
+        use gol::write::Write;
+
+        /// A mostly empty board with a block in one corner and a glider heading across it.
+        fn sparse_board(topology: Topology) -> GameOfLife {
+            let mut rows = vec![vec![false; 40]; 32];
+            for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+                rows[y + 4][x + 4] = true;
+            }
+            for (x, y) in [(30, 26), (31, 26), (30, 27), (31, 27)] {
+                rows[y][x] = true;
+            }
+            GameOfLife::from(rows).with_topology(topology)
+        }
+
+        #[test]
+        fn matches_full_transition() {
+            for topology in [Topology::Bounded, Topology::Torus, Topology::CrossSurface] {
+                for tile_size in [1, 7, 100] {
+                    let mut expected = sparse_board(topology);
+                    let mut active = ActiveLife::new(expected.clone()).with_tile_size(tile_size);
+                    for _ in 0..80 {
+                        expected.step();
+                        active.step();
+                        assert_eq!(active.board(), &expected);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn skips_settled_tiles() -> Result<(), gol::Error> {
+            let board = sparse_board(Topology::Bounded);
+            let mut active = ActiveLife::new(board.clone()).with_tile_size(8);
+            active.step();
+            assert_eq!(active.last_stats().computed, 20);
+            active.step_n(4);
+            // Only the tiles around the glider are left to compute.
+            assert!(active.last_stats().computed <= 9);
+            assert_eq!(
+                active.last_stats().computed + active.last_stats().skipped,
+                20
+            );
+
+            active.write_cell(20, 20, true)?;
+            let mut expected = active.board().clone();
+            expected.step();
+            active.step();
+            assert_eq!(active.board(), &expected);
+            assert_eq!(
+                active.total_stats().computed + active.total_stats().skipped,
+                6 * 20
+            );
+            Ok(())
+        }
+    }
+
+    mod generations {
+        use gol::generations::{Generations, GenerationsLife};
+        use gol::grid::Grid;
+        use gol::io::{read_gol, read_gol_generations, write_gol_generations};
+        use gol::rule::Rule;
+        use gol::write::Write;
+
+        #[test]
+        fn parses_and_prints_rulestrings() -> Result<(), gol::Error> {
+            assert_eq!("B2/S/3".parse::<Generations>()?, Generations::BRIANS_BRAIN);
+            assert_eq!("345/2/4".parse::<Generations>()?, Generations::STAR_WARS);
+            assert_eq!("B2/S345/C4".parse::<Generations>()?, Generations::STAR_WARS);
+            assert_eq!(Generations::STAR_WARS.to_string(), "B2/S345/4");
+            assert_eq!(Generations::new(Rule::CONWAY, 256)?.states(), 256);
+            assert!("B2/S/257".parse::<Generations>().is_err());
+            assert!("B2/S/1".parse::<Generations>().is_err());
+            assert!("B2/S".parse::<Generations>().is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn dying_cells_age_and_block_births() -> Result<(), gol::Error> {
+            let grid = Grid::from(vec![vec![0, 0, 0, 0], vec![0, 1, 1, 0], vec![0, 0, 0, 0]]);
+            let mut board = GenerationsLife::from(grid).with_rule(Generations::BRIANS_BRAIN)?;
+            board.step();
+            let expected = vec![0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1, 0];
+            assert_eq!(board.iter().collect::<Vec<_>>(), expected);
+            assert_eq!(board.population(), 4);
+            board.step();
+            // The dying cells have died rather than being born into, while the outer cells of the
+            // middle row see two live cells and are born.
+            let expected = vec![0, 2, 2, 0, 1, 0, 0, 1, 0, 2, 2, 0];
+            assert_eq!(board.iter().collect::<Vec<_>>(), expected);
+            assert!(board.write_cell(0, 0, 3).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn treats_states_past_the_rule_as_dead() {
+            // The corner in state 2 isn't alive under Conway's rules, so the other three cells
+            // only see two live neighbors each, and it sees three and is born.
+            let mut board = GenerationsLife::from(Grid::from(vec![vec![1, 1], vec![1, 2]]));
+            board.step();
+            assert_eq!(board.iter().collect::<Vec<_>>(), [1, 1, 1, 1]);
+
+            let mut board = GenerationsLife::from(Grid::from(vec![vec![0, 0, 0], vec![0, 7, 0]]));
+            board.step();
+            assert_eq!(board.iter().collect::<Vec<_>>(), [0; 6]);
+            assert_eq!(Generations::BRIANS_BRAIN.next_state(3, 2), 1);
+            assert_eq!(Generations::BRIANS_BRAIN.next_state(3, 1), 0);
+        }
+
+        #[test]
+        fn round_trips_up_to_256_states() -> Result<(), gol::Error> {
+            let rule = "B3/S23/256".parse()?;
+            let rows = (0..16)
+                .map(|y| (0..16).map(|x| (y * 16 + x) as u8).collect())
+                .collect::<Vec<Vec<u8>>>();
+            let board = GenerationsLife::from(Grid::from(rows)).with_rule(rule)?;
+            let mut data = Vec::new();
+            write_gol_generations(&mut data, &board)?;
+            assert_eq!(read_gol_generations(&data[..])?, board);
+
+            // As a two state board, only cells in state 1 are alive.
+            let life = read_gol(&data[..])?;
+            assert_eq!(life.iter().filter(|c| *c).count(), 1);
+
+            let life = super::soup(9, 7, 3);
+            let board = GenerationsLife::from(&life);
+            let mut data = Vec::new();
+            write_gol_generations(&mut data, &board)?;
+            assert_eq!(read_gol(&data[..])?, life);
+            assert_eq!(read_gol_generations(&data[..])?, board);
+            Ok(())
+        }
+    }
+
+    mod parallel {
+        use gol::topology::Topology;
+
+        #[test]
+        fn matches_serial_transition() {
+            for topology in [Topology::Bounded, Topology::Torus, Topology::KleinBottle] {
+                let board = super::soup(37, 29, 7).with_topology(topology);
+                let serial = board.transition();
+                for threads in [0, 1, 2, 3, 8, 29, 64] {
+                    assert_eq!(board.transition_parallel(threads), serial);
+                }
+            }
+            let empty = super::board(&[]);
+            assert_eq!(empty.transition_parallel(4), empty.transition());
+        }
+    }
+
+    mod stream {
+        use std::io::Cursor;
+
+        use gol::io::{
+            read_gol, read_gol_with_metadata, run_gol, transition_gol, write_gol_with_metadata,
+            write_gol_with_version, Metadata, Version,
+        };
+        use gol::Error;
+
+        #[test]
+        fn matches_in_memory_transition() -> Result<(), Error> {
+            for version in [Version::V1, Version::V2] {
+                let board = super::soup(21, 17, 5);
+                let mut input = Vec::new();
+                write_gol_with_version(&mut input, &board, version)?;
+                let mut output = Vec::new();
+                transition_gol(&input[..], &mut output)?;
+                assert_eq!(read_gol(&output[..])?, board.transition());
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn ping_pongs_between_files() -> Result<(), Error> {
+            let rule = "B36/S23".parse()?;
+            let mut board = super::soup(16, 30, 6).with_rule(rule);
+            let metadata = Metadata {
+                rule: Some(rule),
+                ..Metadata::default()
+            };
+            let mut source = Vec::new();
+            write_gol_with_metadata(&mut source, &board, &metadata)?;
+            let (mut source, mut scratch) = (Cursor::new(source), Cursor::new(Vec::new()));
+
+            let result = run_gol(&mut source, &mut scratch, 5)?;
+            result.set_position(0);
+            let (read, metadata) = read_gol_with_metadata(result)?;
+            for _ in 0..5 {
+                board = board.transition();
+            }
+            assert_eq!(read, board);
+            assert_eq!(metadata.generation, Some(5));
+            Ok(())
+        }
+
+        #[test]
+        fn reports_truncated_input() {
+            let data = b"GOFL\x00\x00\x00\x03\x00\x00\x00\x03\x00\x01\x00\x00";
+            assert!(matches!(
+                transition_gol(&data[..], Vec::new()),
+                Err(Error::TruncatedBody {
+                    expected: 9,
+                    got: 4
+                })
+            ));
+        }
+    }
+
+    mod neighborhood {
+        use std::io::Cursor;
+
+        use gol::bit_board::BitBoard;
+        use gol::game_of_life::GameOfLife;
+        use gol::generations::{Generations, GenerationsLife};
+        use gol::hashlife::HashLife;
+        use gol::io::{transition_gol, write_gol, write_gol_with_metadata, GoLFile, Metadata};
+        use gol::iter::IntoTransitionIter;
+        use gol::neighborhood::Neighborhood;
+        use gol::read::Read;
+        use gol::rule::Rule;
+        use gol::sparse::SparseLife;
+        use gol::topology::Topology;
+
+        /// Transitions the board by counting every cell of the neighborhood by hand.
+        fn by_hand(board: &GameOfLife) -> Vec<bool> {
+            let (width, height) = (board.width(), board.height());
+            let cells: Vec<bool> = board.iter().collect();
+            let rule = board.rule();
+            (0..height * width)
+                .map(|i| {
+                    let (x, y) = ((i % width) as isize, (i / width) as isize);
+                    let alive = rule
+                        .neighborhood()
+                        .offsets()
+                        .filter_map(|(dx, dy)| board.topology().wrap(x + dx, y + dy, width, height))
+                        .filter(|(x, y)| cells[y * width + x])
+                        .count();
+                    rule.next_state(cells[i], alive)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn parses_and_prints_rulestrings() -> Result<(), gol::Error> {
+            let hex: Rule = "B2/S34H".parse()?;
+            assert_eq!(hex.neighborhood(), Neighborhood::Hexagonal);
+            assert!(hex.is_birth(2) && hex.is_survival(4));
+            assert_eq!(hex.to_string(), "B2/S34H");
+            let von_neumann: Rule = "B1/S1V".parse()?;
+            assert_eq!(von_neumann.neighborhood(), Neighborhood::VonNeumann(1));
+            assert_eq!(von_neumann.to_string(), "B1/S1V");
+            assert!("B7/S3H".parse::<Rule>().is_err());
+            assert!("B3/S5V".parse::<Rule>().is_err());
+
+            let hrot: Rule = "R2,C0,S6-9,B7-8,NM".parse()?;
+            assert_eq!(hrot.neighborhood(), Neighborhood::Moore(2));
+            assert!(hrot.is_birth(8) && !hrot.is_birth(9) && hrot.is_survival(9));
+            assert_eq!(hrot.to_string().parse::<Rule>()?, hrot);
+            assert_eq!("R1,C2,S2-3,B3,NM".parse::<Rule>()?, Rule::CONWAY);
+            assert!("R4,C0,S1,B1,NM".parse::<Rule>().is_err());
+            assert!("R1,C3,S1,B1,NM".parse::<Rule>().is_err());
+
+            assert_eq!(Neighborhood::Moore(2).size(), 24);
+            assert_eq!(Neighborhood::VonNeumann(2).size(), 12);
+            assert!(!Neighborhood::Hexagonal.contains(1, -1));
+            assert!(Rule::new(&[6], &[2])?
+                .with_neighborhood(Neighborhood::Hexagonal)
+                .is_ok());
+            assert!(Rule::new(&[7], &[2])?
+                .with_neighborhood(Neighborhood::Hexagonal)
+                .is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn boards_count_through_the_neighborhood() -> Result<(), gol::Error> {
+            let rules = [
+                "B2/S34H",
+                "B1/S1V",
+                "R2,C0,S6-9,B7-8,NM",
+                "R3,C0,S2-5,B3-4,NN",
+                // Only the cells above and to the right of each cell.
+                "R1,C0,S1,B1-2,N@60000",
+            ];
+            for rule in rules {
+                let rule: Rule = rule.parse()?;
+                for topology in [Topology::Bounded, Topology::Torus] {
+                    let board = super::soup(23, 19, 11)
+                        .with_rule(rule)
+                        .with_topology(topology);
+                    let expected = by_hand(&board);
+                    let next = board.transition();
+                    assert_eq!(next.iter().collect::<Vec<_>>(), expected);
+                    assert_eq!(board.transition_parallel(3), next);
+                    assert_eq!(GameOfLife::from(&BitBoard::from(&board).transition()), next);
+                    let iterated: Vec<_> = board
+                        .clone()
+                        .into_transition_iter()
+                        .with_rule(rule)
+                        .map(|(_, _, alive)| alive)
+                        .collect();
+                    if topology == Topology::Bounded {
+                        assert_eq!(iterated, expected);
+                    }
+
+                    let mut generations =
+                        GenerationsLife::from(&board).with_rule(Generations::new(rule, 2)?)?;
+                    generations.step();
+                    assert_eq!(generations, GenerationsLife::from(&next));
+                }
+
+                let board = super::soup(20, 20, 4).with_rule(rule);
+                let sparse = SparseLife::try_from(&board)?;
+                let wide = sparse.window(-10, -10, 40, 40);
+                assert_eq!(
+                    sparse.transition().window(-10, -10, 40, 40),
+                    wide.transition()
+                );
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn files_wrap_every_neighborhood() -> Result<(), gol::Error> {
+            let rule: Rule = "B2/S34H".parse()?;
+            for topology in [Topology::Torus, Topology::KleinBottle] {
+                let mut board = super::soup(17, 13, 6)
+                    .with_rule(rule)
+                    .with_topology(topology);
+                let mut data = Vec::new();
+                write_gol(&mut data, &board)?;
+                let mut file =
+                    GoLFile::with_max_capacity(Cursor::new(data), 40)?.with_topology(topology);
+                let mut next = Vec::new();
+                for y in 0..board.height() {
+                    for x in 0..board.width() {
+                        let neighborhood = file.read_neighborhood(x, y, rule.neighborhood())?;
+                        assert_eq!(
+                            neighborhood,
+                            board.read_neighborhood(x, y, rule.neighborhood())?
+                        );
+                        let alive = neighborhood.iter().filter(|c| **c == Some(true)).count();
+                        next.push(rule.next_state(file.read_cell(x, y)?, alive));
+                    }
+                }
+                assert_eq!(board.transition().iter().collect::<Vec<_>>(), next);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn engines_without_the_reach_reject_large_neighborhoods() -> Result<(), gol::Error> {
+            for rule in ["B2/S34H", "B2/S1V"] {
+                let soup = super::soup(12, 12, 9);
+                let mut board = vec![vec![false; 64]; 64];
+                for (i, alive) in soup.iter().enumerate() {
+                    board[26 + i / 12][26 + i % 12] = alive;
+                }
+                let mut board = GameOfLife::from(board).with_rule(rule.parse()?);
+                let mut universe = HashLife::try_from(&board)?;
+                universe.advance_by(12)?;
+                board.step_n(12);
+                assert_eq!(universe.window(0, 0, 64, 64), board);
+            }
+
+            let moore: Rule = "R2,C0,S6-9,B7-8,NM".parse()?;
+            assert!(HashLife::new().with_rule(moore).is_err());
+            let metadata = Metadata {
+                rule: Some(moore),
+                ..Metadata::default()
+            };
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &super::soup(8, 8, 1), &metadata)?;
+            assert!(matches!(
+                transition_gol(&data[..], Vec::new()),
+                Err(gol::Error::Unsupported(_))
+            ));
+            Ok(())
+        }
+    }
+
+    mod ltl {
+        use gol::game_of_life::GameOfLife;
+        use gol::ltl::{LargerThanLife, LtlLife, Shape};
+        use gol::neighborhood::Neighborhood;
+        use gol::read::Read;
+        use gol::rule::Rule;
+        use gol::topology::Topology;
+
+        /// Transitions the board by counting every cell of the neighborhood by hand.
+        fn by_hand(board: &LtlLife) -> Vec<bool> {
+            let (width, height) = (board.width(), board.height());
+            let cells: Vec<bool> = board.iter().collect();
+            let rule = board.rule();
+            let r = rule.radius() as isize;
+            (0..height * width)
+                .map(|i| {
+                    let (x, y) = ((i % width) as isize, (i / width) as isize);
+                    let count = (-r..=r)
+                        .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                        .filter(|(dx, dy)| rule.shape() == Shape::Moore || dx.abs() + dy.abs() <= r)
+                        .filter(|(dx, dy)| rule.middle() || (*dx, *dy) != (0, 0))
+                        .filter_map(|(dx, dy)| board.topology().wrap(x + dx, y + dy, width, height))
+                        .filter(|(x, y)| cells[y * width + x])
+                        .count();
+                    rule.next_state(cells[i], count as u32)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn parses_and_prints_rulestrings() -> Result<(), gol::Error> {
+            let bosco: LargerThanLife = "R5,C0,M1,S34..58,B34..45,NM".parse()?;
+            assert_eq!(bosco, LargerThanLife::bosco());
+            assert_eq!(bosco.to_string(), "R5,C0,M1,S34-58,B34-45,NM");
+            assert_eq!(bosco.size(), 121);
+            let diamond: LargerThanLife = "R7,C0,S10-20,30,B15-18,NN".parse()?;
+            assert_eq!(diamond.shape(), Shape::VonNeumann);
+            assert_eq!(diamond.size(), 112);
+            assert!(diamond.is_survival(30) && !diamond.is_survival(21));
+            assert_eq!(diamond.to_string().parse::<LargerThanLife>()?, diamond);
+            assert!("R5,C3,M1,S34..58,B34..45,NM"
+                .parse::<LargerThanLife>()
+                .is_err());
+            assert!("R5,C0,M1,S34..58,B34..45,NH"
+                .parse::<LargerThanLife>()
+                .is_err());
+            assert!("R501,C0,M1,S34..58,B34..45,NM"
+                .parse::<LargerThanLife>()
+                .is_err());
+            assert!("C0,M1,S34..58,B34..45,NM"
+                .parse::<LargerThanLife>()
+                .is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn matches_life_like_rules() -> Result<(), gol::Error> {
+            for rule in [
+                "R1,C0,S2-3,B3,NM",
+                "R2,C0,S6-9,B7-8,NM",
+                "R3,C0,S2-5,B3-4,NN",
+            ] {
+                for topology in [Topology::Bounded, Topology::Torus, Topology::KleinBottle] {
+                    let mut board = super::soup(24, 20, 2)
+                        .with_rule(rule.parse::<Rule>()?)
+                        .with_topology(topology);
+                    let mut ltl = LtlLife::from(&board).with_rule(rule.parse()?);
+                    for _ in 0..8 {
+                        board.step();
+                        ltl.step();
+                    }
+                    assert_eq!(GameOfLife::from(&ltl), board.with_rule(Rule::CONWAY));
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn counts_large_neighborhoods() -> Result<(), gol::Error> {
+            let rules = [
+                LargerThanLife::bosco(),
+                "R6,C0,M0,S20-40,B25-32,NN".parse()?,
+            ];
+            for rule in rules {
+                for topology in [Topology::Bounded, Topology::Torus] {
+                    let mut board = LtlLife::from(&super::soup(30, 17, 8))
+                        .with_rule(rule.clone())
+                        .with_topology(topology);
+                    for _ in 0..4 {
+                        let expected = by_hand(&board);
+                        board.step();
+                        assert_eq!(board.iter().collect::<Vec<_>>(), expected);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn counts_diamonds_on_thin_strips() -> Result<(), gol::Error> {
+            let rule: LargerThanLife = "R9,C0,M1,S5-12,B6-9,NN".parse()?;
+            for topology in [Topology::Bounded, Topology::Torus, Topology::KleinBottle] {
+                let mut board = LtlLife::from(&super::soup(150, 3, 12))
+                    .with_rule(rule.clone())
+                    .with_topology(topology);
+                for _ in 0..3 {
+                    let expected = by_hand(&board);
+                    board.step();
+                    assert_eq!(board.iter().collect::<Vec<_>>(), expected);
+                }
+
+                let neighborhood = Neighborhood::VonNeumann(2);
+                let life = GameOfLife::from(&board);
+                for (x, y) in [(0, 0), (149, 2), (70, 1)] {
+                    assert_eq!(
+                        board.read_neighborhood(x, y, neighborhood)?,
+                        life.clone().read_neighborhood(x, y, neighborhood)?
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+
+    mod isotropic {
+        use gol::game_of_life::GameOfLife;
+        use gol::isotropic::{Isotropic, IsotropicLife};
+        use gol::rule::Rule;
+        use gol::topology::Topology;
+
+        /// The neighborhoods, without the cell itself, that cause a birth under the rule.
+        fn births(rule: &str) -> Result<Vec<usize>, gol::Error> {
+            let rule: Isotropic = rule.parse()?;
+            Ok((0..512)
+                .filter(|index| index & (1 << 4) == 0 && rule.next_state(*index))
+                .collect())
+        }
+
+        #[test]
+        fn parses_and_prints_rulestrings() -> Result<(), gol::Error> {
+            let rule: Isotropic = "B2-a/S12".parse()?;
+            assert_eq!(rule.to_string(), "B2-a/S12");
+            assert_eq!("b3/s23".parse::<Isotropic>()?.to_string(), "B3/S23");
+            assert_eq!("B2ceki3/S".parse::<Isotropic>()?.to_string(), "B2-an3/S");
+            assert_eq!("S2-i34q/B3".parse::<Isotropic>()?.to_string(), "B3/S2-i34q");
+            assert_eq!(Isotropic::try_from(Rule::CONWAY)?, Isotropic::default());
+            assert!("B2z/S".parse::<Isotropic>().is_err());
+            assert!("B2-/S".parse::<Isotropic>().is_err());
+            assert!("B9/S".parse::<Isotropic>().is_err());
+            assert!(Isotropic::try_from("B2/S34H".parse::<Rule>()?).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn splits_neighborhoods_into_51_configurations() -> Result<(), gol::Error> {
+            let letters = ["", "ce", "ceaikn", "ceaiknjqry", "ceaiknjqrytwz"];
+            let mut configurations = 0;
+            for count in 0..=8 {
+                let letters = letters[usize::min(count, 8 - count)];
+                let mut seen = Vec::new();
+                for letter in letters.chars().map(String::from).chain(
+                    // Counts without letters have a single configuration.
+                    letters.is_empty().then(String::new),
+                ) {
+                    let births = births(&format!("B{count}{letter}/S"))?;
+                    assert!(!births.is_empty());
+                    assert!(births.iter().all(|index| !seen.contains(index)));
+                    seen.extend(births);
+                    configurations += 1;
+                }
+                seen.sort_unstable();
+                assert_eq!(seen, births(&format!("B{count}/S"))?);
+            }
+            assert_eq!(configurations, 51);
+
+            // Corners and edges, with bit 0 the top left neighbor and bit 1 the top neighbor.
+            assert_eq!(births("B1c/S")?, [1, 4, 64, 256]);
+            assert_eq!(births("B1e/S")?, [2, 8, 32, 128]);
+            assert_eq!(births("B2c/S")?, [5, 65, 260, 320]);
+            assert_eq!(births("B7c/S")?, [239, 431, 491, 494]);
+            Ok(())
+        }
+
+        #[test]
+        fn matches_totalistic_rules() -> Result<(), gol::Error> {
+            for rule in [Rule::CONWAY, Rule::HIGHLIFE, Rule::DAY_AND_NIGHT] {
+                for topology in [Topology::Bounded, Topology::Torus] {
+                    let mut board = super::soup(25, 21, 12)
+                        .with_rule(rule)
+                        .with_topology(topology);
+                    let mut isotropic = IsotropicLife::from(&board).with_rule(rule.try_into()?);
+                    for _ in 0..10 {
+                        board.step();
+                        isotropic.step();
+                    }
+                    assert_eq!(GameOfLife::from(&isotropic), board.with_rule(Rule::CONWAY));
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn configurations_change_the_outcome() -> Result<(), gol::Error> {
+            // Under B2-a, two live cells apart give birth between them, but two side by side
+            // can't give birth at all.
+            let board = super::board(&[".....", ".O.O.", "....."]);
+            let next = IsotropicLife::from(&board)
+                .with_rule("B2-a/S12".parse()?)
+                .transition();
+            assert_eq!(
+                GameOfLife::from(&next),
+                super::board(&["..O..", "..O..", "..O.."])
+            );
+            let board = super::board(&["....", ".OO.", "...."]);
+            let next = IsotropicLife::from(&board)
+                .with_rule("B2-a/S12".parse()?)
+                .transition();
+            assert_eq!(
+                GameOfLife::from(&next),
+                super::board(&["....", ".OO.", "...."])
+            );
+            Ok(())
+        }
+    }
+
+    mod rule_table {
+        use std::collections::HashSet;
+
+        use gol::grid::Grid;
+        use gol::read::Read;
+        use gol::rule_table::{read_rule_table, RuleTable, Symmetry, TableLife};
+        use gol::topology::Topology;
+
+        /// Langton's initial loop, with spaces for empty cells.
+        const LOOP: [&str; 10] = [
+            " 22222222      ",
+            "2170140142     ",
+            "2022222202     ",
+            "272    212     ",
+            "212    212     ",
+            "202    212     ",
+            "272    212     ",
+            "21222222122222 ",
+            "207107107111112",
+            " 2222222222222 ",
+        ];
+
+        fn states(rows: &[&str]) -> Vec<Vec<u8>> {
+            rows.iter()
+                .map(|row| row.bytes().map(|b| b.saturating_sub(b'0')).collect())
+                .collect()
+        }
+
+        /// Whether the loop sits on the board with its top left corner at `(x, y)`.
+        fn has_loop(board: &mut TableLife, x: usize, y: usize) -> bool {
+            states(&LOOP).iter().enumerate().all(|(dy, row)| {
+                row.iter().enumerate().all(|(dx, state)| {
+                    *state == 0 || board.read_cell(x + dx, y + dy).ok() == Some(*state)
+                })
+            })
+        }
+
+        #[test]
+        fn parses_tables() -> Result<(), gol::Error> {
+            let table = "@RULE Test
+                # Comments are ignored.
+                @TABLE
+                n_states:3
+                neighborhood:Moore
+                symmetries:rotate4
+                var a={1,2}
+                var b={0,a}
+                0,1,0,0,0,0,0,0,0,1
+                a,b,b,b,b,b,b,b,b,b
+                @COLORS
+                1 255 0 0
+                0 10 20 30 40 50 60
+                @ICONS
+                whatever";
+            let rule = read_rule_table(table.as_bytes())?;
+            assert_eq!(rule.name(), "Test");
+            assert_eq!(rule.states(), 3);
+            assert_eq!(rule.symmetry(), Symmetry::Rotate4);
+            assert_eq!(rule.color(1), Some([255, 0, 0]));
+            assert_eq!(rule.color(0), None);
+            // Births from a single orthogonal neighbor in any rotation, but not a diagonal one.
+            assert_eq!(rule.transition(0, &[0, 0, 1, 0, 0, 0, 0, 0]), Some(1));
+            assert_eq!(rule.transition(0, &[0, 0, 0, 0, 0, 0, 1, 0]), Some(1));
+            assert_eq!(rule.transition(0, &[0, 1, 0, 0, 0, 0, 0, 0]), None);
+            // Bound variables must take the same state everywhere.
+            assert_eq!(rule.transition(2, &[1; 8]), Some(1));
+            assert_eq!(rule.transition(2, &[1, 1, 1, 1, 1, 1, 1, 2]), None);
+            assert_eq!(rule.next_state(2, &[1, 1, 1, 1, 1, 1, 1, 2]), 2);
+
+            let bad = [
+                "@RULE NoTable",
+                "@TABLE\nneighborhood:Moore\n0,0,0,0,0,0,0,0,0,0",
+                "@TABLE\nn_states:2\n0,0,0,0,0,0,0,0,0,2",
+                "@TABLE\nn_states:2\n0,0,0,0,0,0,0,0,0",
+                "@TABLE\nn_states:2\nvar a={0,1}\n0,0,0,0,0,0,0,0,0,a",
+                "@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:rotate8",
+                "@TABLE\nn_states:2\nneighborhood:hexagonal",
+            ];
+            for table in bad {
+                assert!(table.parse::<RuleTable>().is_err(), "{table}");
+            }
+            // Transitions without commas are split a character at a time.
+            assert!(matches!(
+                "@TABLE\nn_states:2\nneighborhood:vonNeumann\n0000é".parse::<RuleTable>(),
+                Err(gol::Error::Malformed(_))
+            ));
+            Ok(())
+        }
+
+        #[test]
+        fn runs_wireworld() -> Result<(), gol::Error> {
+            let rule = RuleTable::wireworld();
+            assert_eq!(rule.symmetry(), Symmetry::Permute);
+            // An electron travels along a wire, one cell a generation.
+            let wire = Grid::from(states(&["21333333"]));
+            let mut board = TableLife::from_grid(wire, rule.clone())?;
+            board.step();
+            assert_eq!(board.grid(), &Grid::from(states(&["32133333"])));
+            board.step_n(5);
+            assert_eq!(board.grid(), &Grid::from(states(&["33333321"])));
+
+            // An electron circulates a ring of eight conductors forever.
+            let ring = Grid::from(states(&[" 21 ", "3  3", "3  3", " 33 "]));
+            let mut board = TableLife::from_grid(ring.clone(), rule.clone())?;
+            board.step_n(10);
+            assert_ne!(board.grid(), &ring);
+            board.step_n(6);
+            assert_eq!(board.grid(), &ring);
+
+            // Three heads around a conductor don't make it a head.
+            let crowded = Grid::from(states(&["111", " 3 "]));
+            let mut board = TableLife::from_grid(crowded, rule.clone())?;
+            board.step();
+            assert_eq!(board.grid(), &Grid::from(states(&["222", " 3 "])));
+            assert!(TableLife::from_grid(Grid::from(states(&["4"])), rule).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn langtons_loop_reproduces() -> Result<(), gol::Error> {
+            let rule = RuleTable::langtons_loops();
+            let mut board = TableLife::new(40, 30, rule.clone()).with_topology(Topology::Torus);
+            for (y, row) in states(&LOOP).into_iter().enumerate() {
+                for (x, state) in row.into_iter().enumerate() {
+                    gol::write::Write::write_cell(&mut board, 5 + x, 12 + y, state)?;
+                }
+            }
+            let mut neighborhoods = HashSet::new();
+            for _ in 0..151 {
+                let (width, height) = (board.width(), board.height());
+                let cells: Vec<u8> = board.iter().collect();
+                let at = |x: usize, y: usize| cells[(y % height) * width + x % width];
+                for y in height..2 * height {
+                    for x in width..2 * width {
+                        let neighbors = [at(x, y - 1), at(x + 1, y), at(x, y + 1), at(x - 1, y)];
+                        neighborhoods.insert((at(x, y), neighbors));
+                    }
+                }
+                board.step();
+            }
+            // Langton's table covers every neighborhood the loop runs into.
+            for (state, neighbors) in neighborhoods {
+                assert!(rule.transition(state, &neighbors).is_some());
+            }
+            // After 151 generations, a copy of the loop has been built to the right of the
+            // original.
+            assert!(!has_loop(&mut board, 5, 12));
+            assert!(has_loop(&mut board, 16, 12));
+            Ok(())
+        }
+    }
+
+    mod margolus {
+        use std::io::Cursor;
+
+        use gol::game_of_life::GameOfLife;
+        use gol::io::{read_gol_with_metadata, write_gol_with_metadata, GoLFile, Metadata};
+        use gol::margolus::{BlockLife, BlockRule};
+        use gol::read::Read;
+        use gol::write::Write;
+
+        #[test]
+        fn parses_and_prints_rules() -> Result<(), gol::Error> {
+            for rule in [
+                BlockRule::CRITTERS,
+                BlockRule::TRON,
+                BlockRule::BILLIARD_BALL_MACHINE,
+            ] {
+                assert_eq!(rule.to_string().parse::<BlockRule>()?, rule);
+                assert!(rule.is_reversible());
+            }
+            assert_eq!(
+                "ms,d15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0".parse::<BlockRule>()?,
+                BlockRule::TRON
+            );
+            assert_eq!(BlockRule::TRON.inverse(), Some(BlockRule::TRON));
+            let merge = BlockRule::new([0, 1, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15])?;
+            assert!(!merge.is_reversible());
+            assert!("MS,D0;1;2".parse::<BlockRule>().is_err());
+            assert!("MS,D16;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0"
+                .parse::<BlockRule>()
+                .is_err());
+            assert!("B3/S23".parse::<BlockRule>().is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn billiard_balls_travel_diagonally() -> Result<(), gol::Error> {
+            let mut ball = GameOfLife::from(vec![vec![false; 8]; 8]);
+            ball.write_cell(0, 0, true)?;
+            let mut board = BlockLife::new(ball, BlockRule::BILLIARD_BALL_MACHINE)?;
+            board.step_n(5)?;
+            assert_eq!(board.phase(), 1);
+            let mut inner = board.into_inner();
+            assert!(inner.read_cell(5, 5)?);
+            assert_eq!(inner.iter().filter(|c| *c).count(), 1);
+
+            assert!(BlockLife::new(super::board(&["...", "..."]), BlockRule::TRON).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn critters_run_backwards() -> Result<(), gol::Error> {
+            let soup = super::soup(16, 12, 5);
+            let mut board = BlockLife::new(soup.clone(), BlockRule::CRITTERS)?;
+            board.step_n(25)?;
+            assert_ne!(board.board(), &soup);
+            for _ in 0..25 {
+                board.step_back()?;
+            }
+            assert_eq!(board.generation(), 0);
+            assert_eq!(board.board(), &soup);
+            assert!(board.step_back().is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn runs_on_files_and_resumes_from_metadata() -> Result<(), gol::Error> {
+            let soup = super::soup(14, 10, 2);
+            let mut memory = BlockLife::new(soup.clone(), BlockRule::CRITTERS)?;
+            memory.step_n(7)?;
+
+            // Run part of the way on a file, then save and pick up where it left off.
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &soup, &Metadata::default())?;
+            let mut file = BlockLife::new(GoLFile::new(Cursor::new(data))?, BlockRule::CRITTERS)?;
+            file.step_n(3)?;
+            let metadata = Metadata {
+                generation: Some(file.generation()),
+                ..Metadata::default()
+            };
+            let saved = GameOfLife::try_from(file.into_inner())?;
+            let mut data = Vec::new();
+            write_gol_with_metadata(&mut data, &saved, &metadata)?;
+
+            let (board, metadata) = read_gol_with_metadata(&data[..])?;
+            let mut resumed = BlockLife::new(board, BlockRule::CRITTERS)?
+                .with_generation(metadata.generation.unwrap_or(0));
+            resumed.step_n(4)?;
+            assert_eq!(resumed, memory);
+            Ok(())
+        }
+    }
+
+    mod reversible {
+        use std::io::Cursor;
+
+        use gol::game_of_life::GameOfLife;
+        use gol::io::{read_gol_reversible, write_gol, write_gol_reversible};
+        use gol::reversible::ReversibleLife;
+        use gol::rule::Rule;
+        use gol::topology::Topology;
+
+        #[test]
+        fn next_is_rule_xor_previous() -> Result<(), gol::Error> {
+            let current = super::soup(12, 10, 3);
+            let previous = super::soup(12, 10, 4);
+            let mut board = ReversibleLife::from_generations(current.clone(), &previous)?;
+            board.step_forward();
+            let expected: Vec<bool> = current
+                .transition()
+                .iter()
+                .zip(previous.iter())
+                .map(|(next, previous)| next ^ previous)
+                .collect();
+            assert_eq!(board.current().iter().collect::<Vec<_>>(), expected);
+            assert_eq!(board.previous(), &current);
+
+            let small = GameOfLife::from(vec![vec![false; 3]; 3]);
+            assert!(ReversibleLife::from_generations(small, &previous).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn runs_backwards_to_the_start() {
+            let soup = super::soup(20, 16, 9).with_topology(Topology::Torus);
+            let mut board = ReversibleLife::new(soup.clone()).with_rule(Rule::HIGHLIFE);
+            board.step_forward_n(60);
+            assert_ne!(
+                board.current().iter().collect::<Vec<_>>(),
+                soup.iter().collect::<Vec<_>>()
+            );
+            board.step_backward_n(60);
+            assert_eq!(board.current(), &soup.clone().with_rule(Rule::HIGHLIFE));
+            assert!(board.previous().iter().all(|alive| !alive));
+
+            // Running past the start is just as well defined.
+            board.step_backward_n(10);
+            board.step_forward_n(10);
+            assert_eq!(board.current(), &soup.with_rule(Rule::HIGHLIFE));
+        }
+
+        #[test]
+        fn files_keep_both_generations() -> Result<(), gol::Error> {
+            let mut board =
+                ReversibleLife::new(super::soup(13, 7, 2)).with_rule(Rule::DAY_AND_NIGHT);
+            board.step_forward_n(3);
+            let mut file = Vec::new();
+            write_gol_reversible(&mut file, &board)?;
+            let mut read = read_gol_reversible(Cursor::new(file))?;
+            assert_eq!(read, board);
+
+            read.step_backward_n(3);
+            board.step_backward_n(3);
+            assert_eq!(read, board);
+
+            let mut file = Vec::new();
+            write_gol(&mut file, board.current())?;
+            let read = read_gol_reversible(Cursor::new(file))?;
+            assert_eq!(
+                read.current().iter().collect::<Vec<_>>(),
+                board.current().iter().collect::<Vec<_>>()
+            );
+            assert_eq!(read.rule(), Rule::CONWAY);
+            assert!(read.previous().iter().all(|alive| !alive));
+            Ok(())
+        }
+    }
+
+    mod cycle {
+        use gol::cycle::{Cycle, CycleDetector};
+        use gol::game_of_life::GameOfLife;
+        use gol::topology::Topology;
+
+        fn padded(rows: &[&str], size: usize) -> GameOfLife {
+            let mut padded = vec![".".repeat(size); size];
+            for (y, row) in rows.iter().enumerate() {
+                padded[y + 4] = format!("....{row}{}", ".".repeat(size - 4 - row.len()));
+            }
+            super::board(&padded.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+
+        #[test]
+        fn still_lifes_and_oscillators() {
+            let mut block = padded(&["OO", "OO"], 10);
+            assert_eq!(CycleDetector::new().run(&mut block), Cycle::Stable);
+
+            let mut blinker = padded(&["OOO"], 10);
+            let mut detector = CycleDetector::new();
+            assert_eq!(detector.run(&mut blinker), Cycle::Oscillating { period: 2 });
+            assert_eq!(detector.generation(), 3);
+
+            let mut empty = GameOfLife::from(vec![vec![false; 5]; 5]);
+            assert_eq!(CycleDetector::new().run(&mut empty), Cycle::Stable);
+
+            // A soup on a torus always settles into something.
+            let mut soup = super::soup(24, 24, 11).with_topology(Topology::Torus);
+            assert_ne!(CycleDetector::new().run(&mut soup), Cycle::Unknown);
+        }
+
+        #[test]
+        fn gliders_translate() {
+            let glider = padded(&[".O.", "..O", "OOO"], 20);
+            let mut board = glider.clone();
+            assert_eq!(
+                CycleDetector::new().run(&mut board),
+                Cycle::Translating {
+                    period: 4,
+                    dx: 1,
+                    dy: 1
+                }
+            );
+
+            // Without translation invariance the glider never repeats until it crashes into the
+            // edge of the board and settles as a block.
+            let mut board = glider;
+            let mut detector = CycleDetector::new().with_translation_invariance(false);
+            assert_eq!(detector.run(&mut board), Cycle::Stable);
+            assert!(detector.generation() > 40);
+        }
+
+        #[test]
+        fn gives_up_at_the_limits() {
+            let blinker = padded(&["OOO"], 10);
+            let mut board = blinker.clone();
+            let mut detector = CycleDetector::new().with_generation_limit(1);
+            assert_eq!(detector.run(&mut board), Cycle::Unknown);
+
+            // A history of one generation can't see a period two oscillator.
+            let mut board = blinker;
+            let mut detector = CycleDetector::new()
+                .with_history(1)
+                .with_generation_limit(50);
+            assert_eq!(detector.run(&mut board), Cycle::Unknown);
+            detector.reset();
+            assert_eq!(detector.generation(), 0);
+        }
+    }
+
+    mod census {
+        use gol::census::{self, Census, UNKNOWN};
+        use gol::cycle::CycleDetector;
+        use gol::game_of_life::GameOfLife;
+
+        /// Places patterns on an empty board at the provided top left corners.
+        fn place(width: usize, height: usize, patterns: &[(usize, usize, &[&str])]) -> GameOfLife {
+            let mut rows = vec![vec![false; width]; height];
+            for (x, y, pattern) in patterns {
+                for (dy, row) in pattern.iter().enumerate() {
+                    for (dx, c) in row.chars().enumerate() {
+                        rows[y + dy][x + dx] |= c == 'O';
+                    }
+                }
+            }
+            rows.into()
+        }
+
+        #[test]
+        fn names_common_objects() {
+            let objects: [(&[&str], &str); 13] = [
+                (&["OO", "OO"], "block"),
+                (&[".O.", "O.O", ".O."], "tub"),
+                (&["OO.", "O.O", ".O."], "boat"),
+                (&["OO.", "O.O", ".OO"], "ship"),
+                (&[".OO.", "O..O", ".OO."], "beehive"),
+                (&[".O..", "O.O.", ".O.O", "..O."], "barge"),
+                (&[".OO.", "O..O", ".O.O", "..O."], "loaf"),
+                (&[".OO.", "O..O", "O..O", ".OO."], "pond"),
+                (&["OOO"], "blinker"),
+                (&[".OOO", "OOO."], "toad"),
+                (&["OO..", "OO..", "..OO", "..OO"], "beacon"),
+                (&[".O.", "..O", "OOO"], "glider"),
+                (
+                    &[".O..O", "O....", "O...O", "OOOO."],
+                    "lightweight spaceship",
+                ),
+            ];
+            for (pattern, expected) in objects {
+                let board = place(12, 12, &[(4, 4, pattern)]);
+                // The spaceship's cells don't all touch, so cells two apart are joined.
+                let found = Census::new().with_distance(2).take(&board);
+                assert_eq!(found.len(), 1, "{expected}");
+                assert_eq!(found[0].name(), Some(expected), "{:?}", found[0].apgcode());
+            }
+            assert_eq!(census::name("xq4_153"), Some("glider"));
+            assert_eq!(census::name("xs4_0"), None);
+        }
+
+        #[test]
+        fn counts_objects_in_any_orientation_and_phase() {
+            let board = place(
+                30,
+                30,
+                &[
+                    (1, 1, &["OO", "OO"]),
+                    (10, 1, &["OOO"]),
+                    (20, 1, &["O", "O", "O"]),
+                    (1, 10, &[".O.", "..O", "OOO"]),
+                    (10, 10, &["OO.", "O.O", "O.."]),
+                    (20, 10, &["OO", "OO"]),
+                    (1, 20, &[".O.", "O.O", "O.O", ".O."]),
+                    (20, 20, &["O"]),
+                ],
+            );
+            let counts = Census::new().count(&board);
+            let expected = [
+                (UNKNOWN, 1),
+                ("xp2_7", 2),
+                ("xq4_153", 2),
+                ("xs4_33", 2),
+                ("xs6_696", 1),
+            ];
+            assert_eq!(
+                counts
+                    .iter()
+                    .map(|(code, n)| (code.as_str(), *n))
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+
+        #[test]
+        fn joins_cells_within_the_distance() {
+            let board = place(8, 4, &[(0, 0, &["OO.OO", "OO.OO"])]);
+            let apart = Census::new().take(&board);
+            assert_eq!(apart.len(), 2);
+            assert_eq!(apart[0].cells(), [(0, 0), (1, 0), (0, 1), (1, 1)]);
+            assert_eq!(apart[1].cells(), [(3, 0), (4, 0), (3, 1), (4, 1)]);
+
+            let together = Census::new().with_distance(2).take(&board);
+            assert_eq!(together.len(), 1);
+            assert_eq!(together[0].cells().len(), 8);
+            assert_eq!(together[0].apgcode(), Some("xs8_rr"));
+            assert_eq!(together[0].name(), None);
+        }
+
+        #[test]
+        fn accounts_for_every_cell_of_a_settled_soup() {
+            let mut soup = super::soup(40, 40, 21);
+            CycleDetector::new().run(&mut soup);
+            let objects = Census::new().take(&soup);
+            assert!(!objects.is_empty());
+            let cells: usize = objects.iter().map(|object| object.cells().len()).sum();
+            assert_eq!(cells, soup.iter().filter(|alive| *alive).count());
+        }
+    }
 }
@@ -0,0 +1,222 @@
+//! # Block Cellular Automata
+//! Block rules, such as Critters, Tron and the billiard ball machine, don't look at each cell's
+//! neighbors. Instead the board is split into 2x2 blocks, and each block is replaced as a whole
+//! according to a table. The blocks alternate between two partitions of the board, the Margolus
+//! neighborhood: even generations use blocks whose top left corners are at even coordinates, and
+//! odd generations shift every block one cell down and to the right.
+//!
+//! A block is numbered by adding 1 for its top left cell, 2 for its top right, 4 for its bottom
+//! left and 8 for its bottom right, so a rule is a table of 16 blocks. Rules are written as in
+//! Golly, `MS,D` followed by the table separated by semicolons, such as Tron's
+//! `MS,D15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0`.
+//!
+//! A rule whose table is a permutation of the blocks is reversible, and can be run backwards.
+
+use std::{fmt, str::FromStr};
+
+use crate::{error::Error, read::Read, write::Write};
+
+/// A block rule, a table from each of the 16 possible 2x2 blocks to the block replacing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockRule {
+    table: [u8; 16],
+}
+
+impl BlockRule {
+    /// Critters, `MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0`.
+    pub const CRITTERS: BlockRule = BlockRule {
+        table: [15, 14, 13, 3, 11, 5, 6, 1, 7, 9, 10, 2, 12, 4, 8, 0],
+    };
+    /// Tron, `MS,D15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0`.
+    pub const TRON: BlockRule = BlockRule {
+        table: [15, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0],
+    };
+    /// The billiard ball machine, `MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15`.
+    pub const BILLIARD_BALL_MACHINE: BlockRule = BlockRule {
+        table: [0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15],
+    };
+
+    /// Creates a rule from the block replacing each block. Every entry must be below 16.
+    pub fn new(table: [u8; 16]) -> Result<Self, Error> {
+        if table.iter().any(|block| *block > 15) {
+            return Err(Error::InvalidRule("Blocks must be between 0 and 15"));
+        }
+        Ok(BlockRule { table })
+    }
+
+    /// The block replacing `block`.
+    pub fn apply(&self, block: u8) -> u8 {
+        self.table[usize::from(block & 15)]
+    }
+
+    /// Whether every block is replaced by a different block, so the rule can be run backwards.
+    pub fn is_reversible(&self) -> bool {
+        self.inverse().is_some()
+    }
+
+    /// The rule undoing this one, if it is reversible.
+    pub fn inverse(&self) -> Option<BlockRule> {
+        let mut table = [None; 16];
+        for (block, next) in self.table.iter().enumerate() {
+            table[usize::from(*next)] = Some(block as u8);
+        }
+        let table = table.into_iter().collect::<Option<Vec<u8>>>()?;
+        Some(BlockRule {
+            table: table.try_into().expect("The table has 16 blocks"),
+        })
+    }
+}
+
+impl FromStr for BlockRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let table = s
+            .get(..4)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("MS,D"))
+            .map(|_| &s[4..])
+            .ok_or(Error::InvalidRule("Block rules start with 'MS,D'"))?;
+        let table = table
+            .split(';')
+            .map(|block| block.trim().parse::<u8>())
+            .collect::<Result<Vec<_>, _>>()
+            .or(Err(Error::InvalidRule("Malformed block rule")))?;
+        let table = table
+            .try_into()
+            .or(Err(Error::InvalidRule("Block rules have 16 entries")))?;
+        BlockRule::new(table)
+    }
+}
+
+impl fmt::Display for BlockRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MS,D")?;
+        for (i, block) in self.table.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{block}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a [BlockRule] on any board that can be read and written, such as a
+/// [GameOfLife](crate::game_of_life::GameOfLife) or a [GoLFile](crate::io::GoLFile), keeping
+/// track of which partition of blocks comes next.
+///
+/// Blocks that cross the edges of the board wrap around to the other side, whatever the board's
+/// own topology, so the board must have an even width and height.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockLife<B> {
+    board: B,
+    rule: BlockRule,
+    generation: u64,
+}
+
+impl<B: Read + Write> BlockLife<B> {
+    /// Wraps a board at generation 0. Fails if the board's width or height is odd.
+    pub fn new(board: B, rule: BlockRule) -> Result<Self, Error> {
+        if board.width() % 2 != 0 || board.height() % 2 != 0 {
+            return Err(Error::Unsupported(
+                "Block rules on boards with an odd width or height",
+            ));
+        }
+        Ok(BlockLife {
+            board,
+            rule,
+            generation: 0,
+        })
+    }
+
+    /// Sets the generation the board is at, such as one read from a .gol file's
+    /// [Metadata](crate::io::Metadata), which decides which partition of blocks comes next.
+    pub fn with_generation(self, generation: u64) -> Self {
+        Self { generation, ..self }
+    }
+
+    /// The rule used when transitioning the board.
+    pub fn rule(&self) -> BlockRule {
+        self.rule
+    }
+
+    /// The number of generations the board has been run for.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The offset of the blocks the next step will use, 0 for blocks at even coordinates and 1
+    /// for blocks shifted down and to the right.
+    pub fn phase(&self) -> usize {
+        (self.generation % 2) as usize
+    }
+
+    /// The underlying board.
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// Unwraps the underlying board.
+    pub fn into_inner(self) -> B {
+        self.board
+    }
+
+    /// Advance the board a single generation in place, then flush it.
+    pub fn step(&mut self) -> Result<(), Error> {
+        let phase = self.phase();
+        apply(&mut self.board, self.rule, phase)?;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) -> Result<(), Error> {
+        for _ in 0..n {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Undo the last generation, by applying the inverse of the rule to the previous partition
+    /// of blocks. Fails if the rule isn't reversible or the board is at generation 0.
+    pub fn step_back(&mut self) -> Result<(), Error> {
+        let inverse = self.rule.inverse().ok_or(Error::Unsupported(
+            "Running irreversible block rules backwards",
+        ))?;
+        let generation = self
+            .generation
+            .checked_sub(1)
+            .ok_or(Error::Unsupported("Running back past generation 0"))?;
+        apply(&mut self.board, inverse, (generation % 2) as usize)?;
+        self.generation = generation;
+        Ok(())
+    }
+}
+
+/// Replaces every block of the partition at `phase`, then flushes the board.
+fn apply<B: Read + Write>(board: &mut B, rule: BlockRule, phase: usize) -> Result<(), Error> {
+    let (width, height) = (board.width(), board.height());
+    for y in (phase..height).step_by(2) {
+        for x in (phase..width).step_by(2) {
+            // The block's cells in the order of their bits, wrapping past the edges.
+            let cells = [
+                (x, y),
+                ((x + 1) % width, y),
+                (x, (y + 1) % height),
+                ((x + 1) % width, (y + 1) % height),
+            ];
+            let mut block = 0;
+            for (bit, (x, y)) in cells.iter().enumerate() {
+                block |= u8::from(board.read_cell(*x, *y)?) << bit;
+            }
+            let next = rule.apply(block);
+            if next != block {
+                for (bit, (x, y)) in cells.iter().enumerate() {
+                    board.write_cell(*x, *y, next & (1 << bit) != 0)?;
+                }
+            }
+        }
+    }
+    board.flush()
+}
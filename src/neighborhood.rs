@@ -0,0 +1,99 @@
+//! # Neighborhoods
+//! Which cells around a cell count as its neighbors. Life-like rules normally use the 8 cells of
+//! the Moore neighborhood, but rules can also use the 4 orthogonal cells of the von Neumann
+//! neighborhood, the 6 cells of a hexagonal grid, a Moore or von Neumann neighborhood of a larger
+//! radius, or any custom set of cells.
+//!
+//! Every neighborhood fits within a radius of [MAX_RADIUS], so it can be described by a mask of
+//! the 7x7 square around the cell. Bit `(dy + 3) * 7 + (dx + 3)` of the mask is set when the cell
+//! at offset `(dx, dy)` is a neighbor. The cell itself is never its own neighbor.
+
+use crate::error::Error;
+
+/// The largest radius a neighborhood can reach.
+pub const MAX_RADIUS: u8 = 3;
+const SIDE: isize = 2 * MAX_RADIUS as isize + 1;
+const CENTER_BIT: u64 = 1 << (SIDE * SIDE / 2);
+
+/// The shape of the neighborhood counted by a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// Every cell within a square of the radius around the cell.
+    Moore(u8),
+    /// Every cell within the radius of the cell, measured in orthogonal steps.
+    VonNeumann(u8),
+    /// The 6 neighbors of a cell on a hexagonal grid, skewed onto the square grid so that the top
+    /// right and bottom left cells aren't neighbors.
+    Hexagonal,
+    /// The cells set in a 7x7 mask, as described in the [module documentation](self).
+    Custom(u64),
+}
+
+impl Default for Neighborhood {
+    fn default() -> Self {
+        Neighborhood::Moore(1)
+    }
+}
+
+impl Neighborhood {
+    /// Checks that the neighborhood fits within [MAX_RADIUS] and has at least one cell.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Neighborhood::Moore(r) | Neighborhood::VonNeumann(r)
+                if !(1..=MAX_RADIUS).contains(r) =>
+            {
+                Err(Error::Unsupported("Neighborhoods with a radius over 3"))
+            }
+            _ if self.size() == 0 => Err(Error::InvalidRule("Neighborhood has no cells")),
+            _ => Ok(()),
+        }
+    }
+
+    /// The mask of the cells in the neighborhood.
+    pub fn mask(&self) -> u64 {
+        let mask = match *self {
+            Neighborhood::Custom(mask) => mask,
+            shape => (0..SIDE * SIDE)
+                .filter(|bit| {
+                    let (dx, dy) = (bit % SIDE - SIDE / 2, bit / SIDE - SIDE / 2);
+                    match shape {
+                        Neighborhood::Moore(r) => dx.abs().max(dy.abs()) <= r as isize,
+                        Neighborhood::VonNeumann(r) => dx.abs() + dy.abs() <= r as isize,
+                        Neighborhood::Hexagonal => dx.abs() <= 1 && dy.abs() <= 1 && dx != -dy,
+                        Neighborhood::Custom(_) => unreachable!(),
+                    }
+                })
+                .fold(0, |mask, bit| mask | 1 << bit),
+        };
+        mask & !CENTER_BIT & ((1 << (SIDE * SIDE)) - 1)
+    }
+
+    /// The number of cells in the neighborhood, and so the most live neighbors a cell can have.
+    pub fn size(&self) -> usize {
+        self.mask().count_ones() as usize
+    }
+
+    /// How far the neighborhood reaches from the cell in any direction.
+    pub fn radius(&self) -> u8 {
+        self.offsets()
+            .map(|(dx, dy)| dx.abs().max(dy.abs()) as u8)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether the cell at the offset is in the neighborhood.
+    pub fn contains(&self, dx: isize, dy: isize) -> bool {
+        let r = MAX_RADIUS as isize;
+        (-r..=r).contains(&dx)
+            && (-r..=r).contains(&dy)
+            && self.mask() & 1 << ((dy + r) * SIDE + dx + r) != 0
+    }
+
+    /// The offsets of the cells in the neighborhood, row by row from the top left.
+    pub fn offsets(&self) -> impl Iterator<Item = (isize, isize)> {
+        let mask = self.mask();
+        (0..SIDE * SIDE)
+            .filter(move |bit| mask & 1 << bit != 0)
+            .map(|bit| (bit % SIDE - SIDE / 2, bit / SIDE - SIDE / 2))
+    }
+}
@@ -1,9 +1,35 @@
+use crate::{error::Error, neighborhood::Neighborhood, utils::adjust_coordinate};
+
 /// A generic way to interact with Game of Life representations.
-pub trait Read {
+///
+/// Cells are `bool` for two state rules, but boards for rules with more states can use any cell
+/// type, such as a `u8` state number.
+pub trait Read<C = bool> {
     /// Reads the cell at the coordinates provided.
-    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, &'static str>;
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<C, Error>;
     /// Reads all eight surrounding cells. If on the board's border, None is returned.
-    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], &'static str>;
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<C>; 8], Error>;
+    /// Reads every cell in the neighborhood, in the order of [Neighborhood::offsets]. Cells off
+    /// the board are None.
+    ///
+    /// The default implementation treats the board as bounded. Boards with other topologies
+    /// should override it.
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<C>>, Error> {
+        self.read_cell(x, y)?;
+        let (width, height) = (self.width(), self.height());
+        neighborhood
+            .offsets()
+            .map(|(dx, dy)| match adjust_coordinate((x, y), dx, dy) {
+                Some((x, y)) if x < width && y < height => self.read_cell(x, y).map(Some),
+                _ => Ok(None),
+            })
+            .collect()
+    }
 
     /// The width of the board.
     fn width(&self) -> usize;
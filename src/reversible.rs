@@ -0,0 +1,156 @@
+//! # Second-Order Reversible Life
+//! Any rule can be made reversible with a trick due to Edward Fredkin: each generation depends on
+//! the two before it, `next = rule(current) XOR previous`. Since XOR undoes itself, the previous
+//! generation can be recovered from the two after it, `previous = rule(current) XOR next`, so the
+//! board can be run backwards as easily as forwards, and runs back to exactly where it started.
+//!
+//! The board has to keep both the current and the previous generation, which is also what gets
+//! saved, see [read_gol_reversible](crate::io::read_gol_reversible).
+
+use crate::{
+    error::Error, game_of_life::GameOfLife, neighborhood::Neighborhood, read::Read, rule::Rule,
+    topology::Topology, write::Write,
+};
+
+/// A board running a life-like rule in second-order reversible mode, keeping both the current and
+/// the previous generation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReversibleLife {
+    current: GameOfLife,
+    previous: GameOfLife,
+}
+
+impl ReversibleLife {
+    /// Starts from `current`, taking on its rule and topology, with every cell of the previous
+    /// generation dead.
+    pub fn new(current: GameOfLife) -> Self {
+        let mut previous = current.clone();
+        previous.cells.fill(false);
+        ReversibleLife { current, previous }
+    }
+
+    /// Starts from both the current and the previous generation. The previous generation takes on
+    /// the current one's rule and topology. Fails if the boards are different sizes.
+    pub fn from_generations(current: GameOfLife, previous: &GameOfLife) -> Result<Self, Error> {
+        if current.width() != previous.width() || current.height() != previous.height() {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut board = ReversibleLife::new(current);
+        board.previous.cells.copy_from_slice(&previous.cells);
+        Ok(board)
+    }
+
+    /// Sets the ruleset used when transitioning the board. Defaults to the current generation's
+    /// rule.
+    pub fn with_rule(self, rule: Rule) -> Self {
+        ReversibleLife {
+            current: self.current.with_rule(rule),
+            previous: self.previous.with_rule(rule),
+        }
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Rule {
+        self.current.rule()
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to the current
+    /// generation's topology.
+    pub fn with_topology(self, topology: Topology) -> Self {
+        ReversibleLife {
+            current: self.current.with_topology(topology),
+            previous: self.previous.with_topology(topology),
+        }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.current.topology()
+    }
+
+    /// The current generation.
+    pub fn current(&self) -> &GameOfLife {
+        &self.current
+    }
+
+    /// The generation before the current one.
+    pub fn previous(&self) -> &GameOfLife {
+        &self.previous
+    }
+
+    /// Advance the board a single generation in place.
+    pub fn step_forward(&mut self) {
+        // The previous generation is replaced by the next one, then the two swap places.
+        xor_transition(&self.current, &mut self.previous);
+        std::mem::swap(&mut self.current, &mut self.previous);
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_forward_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step_forward();
+        }
+    }
+
+    /// Undo a single generation in place, so the previous generation becomes the current one.
+    pub fn step_backward(&mut self) {
+        // The current generation is replaced by the one before the previous, then the two swap
+        // places.
+        xor_transition(&self.previous, &mut self.current);
+        std::mem::swap(&mut self.current, &mut self.previous);
+    }
+
+    /// Undo `n` generations in place.
+    pub fn step_backward_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step_backward();
+        }
+    }
+}
+
+/// XORs each cell of `target` with the next state of the same cell of `board`.
+fn xor_transition(board: &GameOfLife, target: &mut GameOfLife) {
+    let width = board.width();
+    for (i, cell) in target.cells.iter_mut().enumerate() {
+        *cell ^= board.next_cell(i % width, i / width);
+    }
+}
+
+impl Read for ReversibleLife {
+    /// Reads the current generation.
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<bool, Error> {
+        self.current.read_cell(x, y)
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<bool>; 8], Error> {
+        self.current.read_neighbors(x, y)
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        self.current.read_neighborhood(x, y, neighborhood)
+    }
+
+    fn width(&self) -> usize {
+        self.current.width()
+    }
+
+    fn height(&self) -> usize {
+        self.current.height()
+    }
+}
+
+impl Write for ReversibleLife {
+    /// Writes to the current generation, leaving the previous one as it is.
+    fn write_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), Error> {
+        self.current.write_cell(x, y, alive)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+//! # Life-like Rules
+//! Rules are described with the standard B/S notation, where the digits following `B` are the
+//! neighbor counts that cause a dead cell to be born and the digits following `S` are the neighbor
+//! counts that let a live cell survive. Conway's Game of Life is `B3/S23`.
+//!
+//! The older S/B notation (`23/3`) is also accepted when parsing.
+//!
+//! A trailing `H` counts the hexagonal neighborhood instead of the Moore neighborhood, and a
+//! trailing `V` the von Neumann neighborhood, so `B2/S34H` is a hexagonal rule. Rules over
+//! larger neighborhoods, whose counts don't fit in single digits, use the HROT notation, such as
+//! `R2,C0,S6-9,B7-8,NM`. See [Neighborhood] for the shapes available.
+
+use std::{fmt, str::FromStr};
+
+use crate::{error::Error, neighborhood::Neighborhood};
+
+/// A life-like ruleset, deciding whether a cell is alive in the next generation based on its
+/// current state and how many of its neighbors are alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u64,
+    survival: u64,
+    neighborhood: Neighborhood,
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub const CONWAY: Rule = Rule::from_masks(0b1000, 0b1100);
+    /// HighLife, `B36/S23`.
+    pub const HIGHLIFE: Rule = Rule::from_masks(0b100_1000, 0b1100);
+    /// Day & Night, `B3678/S34678`.
+    pub const DAY_AND_NIGHT: Rule = Rule::from_masks(0b1_1100_1000, 0b1_1101_1000);
+    /// Seeds, `B2/S`.
+    pub const SEEDS: Rule = Rule::from_masks(0b100, 0);
+
+    /// Creates a rule from the neighbor counts that cause a birth and the neighbor counts that let
+    /// a cell survive. Counts must be between 0 and 8.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_masks(to_mask(birth)?, to_mask(survival)?))
+    }
+
+    pub(crate) const fn from_masks(birth: u64, survival: u64) -> Self {
+        Rule {
+            birth,
+            survival,
+            neighborhood: Neighborhood::Moore(1),
+        }
+    }
+
+    /// Sets the neighborhood counted by the rule. Defaults to the Moore neighborhood. Fails if
+    /// the neighborhood is invalid or the rule has counts higher than it has cells.
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Result<Self, Error> {
+        neighborhood.validate()?;
+        if (self.birth | self.survival) >> (neighborhood.size() + 1) != 0 {
+            return Err(Error::InvalidRule(
+                "Neighbor counts can't be larger than the neighborhood",
+            ));
+        }
+        Ok(Self {
+            neighborhood,
+            ..self
+        })
+    }
+
+    /// The neighborhood counted by the rule.
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Whether a dead cell with `alive_neighbors` live neighbors comes to life.
+    pub fn is_birth(&self, alive_neighbors: usize) -> bool {
+        alive_neighbors < 64 && self.birth & (1 << alive_neighbors) != 0
+    }
+
+    /// Whether a live cell with `alive_neighbors` live neighbors survives.
+    pub fn is_survival(&self, alive_neighbors: usize) -> bool {
+        alive_neighbors < 64 && self.survival & (1 << alive_neighbors) != 0
+    }
+
+    /// Returns the next state of a cell given its current state and number of live neighbors.
+    pub fn next_state(&self, current: bool, alive_neighbors: usize) -> bool {
+        if current {
+            self.is_survival(alive_neighbors)
+        } else {
+            self.is_birth(alive_neighbors)
+        }
+    }
+
+    /// Aliveness check, returns whether or not the provided cell survives, dies, or comes to life.
+    /// Neighbors that are `None` (i.e. off the board) are treated as dead.
+    pub fn is_alive(&self, current: bool, neighbors: &[Option<bool>]) -> bool {
+        let alive_neighbors = neighbors.iter().flatten().filter(|n| **n).count();
+        self.next_state(current, alive_neighbors)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with(['R', 'r']) && s.contains(',') {
+            return Hrot::from_str(s)?.to_rule();
+        }
+        let (s, neighborhood) = match s.chars().last() {
+            Some('H' | 'h') => (&s[..s.len() - 1], Neighborhood::Hexagonal),
+            Some('V' | 'v') => (&s[..s.len() - 1], Neighborhood::VonNeumann(1)),
+            _ => (s, Neighborhood::Moore(1)),
+        };
+        let (birth, survival) = split_rulestring(s)?;
+        Self::from_masks(parse_counts(birth)?, parse_counts(survival)?)
+            .with_neighborhood(neighborhood)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match self.neighborhood {
+            Neighborhood::Moore(1) => "",
+            Neighborhood::VonNeumann(1) => "V",
+            Neighborhood::Hexagonal => "H",
+            _ => return write!(f, "{}", Hrot::from(*self)),
+        };
+        write!(f, "B")?;
+        write_counts(f, self.birth)?;
+        write!(f, "/S")?;
+        write_counts(f, self.survival)?;
+        write!(f, "{suffix}")
+    }
+}
+
+/// The shape of a neighborhood in HROT notation, which unlike [Neighborhood] can be of any
+/// radius.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Shape {
+    Moore,
+    VonNeumann,
+    Hexagonal,
+    Custom(u64),
+}
+
+/// A rule in the HROT notation used for Larger than Life and other higher range outer totalistic
+/// rules, `R<radius>,C<states>,M<0|1>,S<counts>,B<counts>,N<shape>`.
+///
+/// Counts are lists of numbers and ranges, such as `S2,4-6` or `B34..45`. The states, middle and
+/// shape fields are optional, defaulting to two states, the cell not counting itself and the
+/// Moore neighborhood. Shapes are `M` for Moore, `N` for von Neumann, `H` for hexagonal and `@`
+/// followed by a hex [Neighborhood] mask.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Hrot {
+    pub radius: u32,
+    pub states: u32,
+    pub middle: bool,
+    pub survival: Vec<(u32, u32)>,
+    pub birth: Vec<(u32, u32)>,
+    pub shape: Shape,
+}
+
+impl Hrot {
+    /// Converts to a life-like rule, which needs a neighborhood within [Neighborhood]'s radius,
+    /// two states and the cell not counting itself.
+    fn to_rule(&self) -> Result<Rule, Error> {
+        if self.states > 2 {
+            return Err(Error::Unsupported("HROT rules with more than two states"));
+        }
+        if self.middle {
+            return Err(Error::Unsupported(
+                "Neighborhoods including the cell itself",
+            ));
+        }
+        let radius = u8::try_from(self.radius).or(Err(Error::Unsupported(
+            "Neighborhoods with a radius over 3",
+        )))?;
+        let neighborhood = match self.shape {
+            Shape::Moore => Neighborhood::Moore(radius),
+            Shape::VonNeumann => Neighborhood::VonNeumann(radius),
+            Shape::Hexagonal if radius == 1 => Neighborhood::Hexagonal,
+            Shape::Hexagonal => {
+                return Err(Error::Unsupported(
+                    "Hexagonal neighborhoods with a radius over 1",
+                ))
+            }
+            Shape::Custom(mask) => Neighborhood::Custom(mask),
+        };
+        let mask = |ranges: &[(u32, u32)]| {
+            ranges.iter().try_fold(0u64, |mask, (low, high)| {
+                (*low..=*high).try_fold(mask, |mask, count| {
+                    (count < 64)
+                        .then(|| mask | 1 << count)
+                        .ok_or(Error::InvalidRule(
+                            "Neighbor counts can't be larger than the neighborhood",
+                        ))
+                })
+            })
+        };
+        Rule::from_masks(mask(&self.birth)?, mask(&self.survival)?).with_neighborhood(neighborhood)
+    }
+}
+
+impl From<Rule> for Hrot {
+    fn from(value: Rule) -> Self {
+        let ranges = |mask: u64| {
+            let mut ranges: Vec<(u32, u32)> = Vec::new();
+            for count in (0..64).filter(|count| mask & (1 << count) != 0) {
+                match ranges.last_mut() {
+                    Some((_, high)) if *high + 1 == count => *high = count,
+                    _ => ranges.push((count, count)),
+                }
+            }
+            ranges
+        };
+        let shape = match value.neighborhood {
+            Neighborhood::Moore(_) => Shape::Moore,
+            Neighborhood::VonNeumann(_) => Shape::VonNeumann,
+            Neighborhood::Hexagonal => Shape::Hexagonal,
+            Neighborhood::Custom(_) => Shape::Custom(value.neighborhood.mask()),
+        };
+        Hrot {
+            radius: value.neighborhood.radius() as u32,
+            states: 2,
+            middle: false,
+            survival: ranges(value.survival),
+            birth: ranges(value.birth),
+            shape,
+        }
+    }
+}
+
+impl FromStr for Hrot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |value: &str| value.trim().parse::<u32>().or(Err(malformed()));
+        let mut radius = None;
+        let mut states = 2;
+        let mut middle = false;
+        let mut survival = Vec::new();
+        let mut birth = Vec::new();
+        let mut shape = Shape::Moore;
+        // Count lists continue over commas, so remember which list is being filled.
+        let mut list = None;
+        for field in s.trim().split(',').map(str::trim) {
+            let Some(prefix) = field.chars().next() else {
+                return Err(malformed());
+            };
+            if prefix.is_ascii_digit() {
+                match list {
+                    Some('S') => survival.push(parse_range(field)?),
+                    Some(_) => birth.push(parse_range(field)?),
+                    None => return Err(malformed()),
+                }
+                continue;
+            }
+            let value = &field[1..];
+            list = None;
+            match prefix.to_ascii_uppercase() {
+                'S' | 'B' => {
+                    let counts = if prefix.eq_ignore_ascii_case(&'S') {
+                        &mut survival
+                    } else {
+                        &mut birth
+                    };
+                    if !value.is_empty() {
+                        counts.push(parse_range(value)?);
+                    }
+                    list = Some(prefix.to_ascii_uppercase());
+                }
+                'R' => radius = Some(number(value)?),
+                'C' => states = number(value)?.max(2),
+                'M' => middle = number(value)? == 1,
+                'N' => {
+                    shape = match value.to_ascii_uppercase().as_str() {
+                        "M" => Shape::Moore,
+                        "N" => Shape::VonNeumann,
+                        "H" => Shape::Hexagonal,
+                        _ => match value.strip_prefix('@') {
+                            Some(hex) => {
+                                Shape::Custom(u64::from_str_radix(hex, 16).or(Err(malformed()))?)
+                            }
+                            None => return Err(Error::Unsupported("HROT neighborhood shape")),
+                        },
+                    }
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        let radius = radius.ok_or(Error::InvalidRule("HROT rule is missing its radius"))?;
+        if radius == 0 {
+            return Err(Error::InvalidRule("HROT radius must be at least 1"));
+        }
+        Ok(Hrot {
+            radius,
+            states,
+            middle,
+            survival,
+            birth,
+            shape,
+        })
+    }
+}
+
+impl fmt::Display for Hrot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges = |ranges: &[(u32, u32)]| {
+            ranges
+                .iter()
+                .map(|(low, high)| match high - low {
+                    0 => format!("{low}"),
+                    _ => format!("{low}-{high}"),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        write!(
+            f,
+            "R{},C{},M{},S{},B{},N",
+            self.radius,
+            if self.states == 2 { 0 } else { self.states },
+            u8::from(self.middle),
+            ranges(&self.survival),
+            ranges(&self.birth)
+        )?;
+        match self.shape {
+            Shape::Moore => write!(f, "M"),
+            Shape::VonNeumann => write!(f, "N"),
+            Shape::Hexagonal => write!(f, "H"),
+            Shape::Custom(mask) => write!(f, "@{mask:x}"),
+        }
+    }
+}
+
+/// Splits a rulestring in B/S notation, or S/B notation with or without prefixes, into its
+/// birth and survival conditions.
+pub(crate) fn split_rulestring(s: &str) -> Result<(&str, &str), Error> {
+    let (first, second) = s
+        .split_once('/')
+        .ok_or(Error::InvalidRule("Rule is missing a '/'"))?;
+    match (first.chars().next(), second.chars().next()) {
+        (Some('B' | 'b'), Some('S' | 's')) => Ok((&first[1..], &second[1..])),
+        (Some('S' | 's'), Some('B' | 'b')) => Ok((&second[1..], &first[1..])),
+        (Some('B' | 'b'), _) | (_, Some('B' | 'b')) => {
+            Err(Error::InvalidRule("Rule is missing an 'S'"))
+        }
+        (Some('S' | 's'), _) | (_, Some('S' | 's')) => {
+            Err(Error::InvalidRule("Rule is missing a 'B'"))
+        }
+        // Without prefixes the rule is in S/B notation.
+        _ => Ok((second, first)),
+    }
+}
+
+fn malformed() -> Error {
+    Error::InvalidRule("Malformed HROT rule")
+}
+
+/// Parses a single count, `a`, or a range of counts, `a-b` or `a..b`.
+fn parse_range(range: &str) -> Result<(u32, u32), Error> {
+    let number = |value: &str| value.trim().parse::<u32>().or(Err(malformed()));
+    let (low, high) = match range.split_once("..").or_else(|| range.split_once('-')) {
+        Some((low, high)) => (number(low)?, number(high)?),
+        None => (number(range)?, number(range)?),
+    };
+    if low > high {
+        return Err(malformed());
+    }
+    Ok((low, high))
+}
+
+fn to_mask(counts: &[u8]) -> Result<u64, Error> {
+    counts.iter().try_fold(0, |mask, count| {
+        if *count > 8 {
+            Err(Error::InvalidRule(
+                "Neighbor counts must be between 0 and 8",
+            ))
+        } else {
+            Ok(mask | 1 << count)
+        }
+    })
+}
+
+fn parse_counts(counts: &str) -> Result<u64, Error> {
+    counts.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(count) if count <= 8 => Ok(mask | 1 << count),
+        _ => Err(Error::InvalidRule(
+            "Neighbor counts must be digits between 0 and 8",
+        )),
+    })
+}
+
+fn write_counts(f: &mut fmt::Formatter<'_>, mask: u64) -> fmt::Result {
+    (0..=8)
+        .filter(|count| mask & (1 << count) != 0)
+        .try_for_each(|count| write!(f, "{count}"))
+}
@@ -0,0 +1,848 @@
+//! # Rule Tables
+//! Rule tables describe multi-state rules as a list of transitions, in the `.rule` file format
+//! used by Golly. A file is split into sections by lines starting with `@`:
+//!
+//! - `@RULE` gives the rule's name, followed by any description.
+//! - `@TABLE` holds the table itself.
+//! - `@COLORS` gives the color of each state, as lines of a state followed by red, green and blue
+//!   values from 0 to 255.
+//!
+//! Any other sections, such as `@ICONS`, are skipped, and `#` starts a comment.
+//!
+//! A table begins with the number of states (`n_states:4`), the neighborhood (`neighborhood:Moore`
+//! or `neighborhood:vonNeumann`) and the symmetries of the transitions
+//! (`symmetries:rotate4reflect`), then declares variables standing for sets of states, such as
+//! `var a={0,1,2}`, and lists the transitions. Each transition is the cell's current state, its
+//! neighbors clockwise from north (N, NE, E, SE, S, SW, W, NW for Moore, and N, E, S, W for von
+//! Neumann) and finally its next state, separated by commas. Tables with fewer than 11 states can
+//! leave the commas out.
+//!
+//! A variable appearing more than once in a transition must take the same state everywhere it
+//! appears, and the next state can be a variable taken from the inputs. The first transition
+//! matching a cell wins, and a cell that no transition matches keeps its state.
+//!
+//! The symmetries are `none`, `rotate4`, `rotate8` (Moore only), `reflect`, `rotate4reflect`,
+//! `rotate8reflect` (Moore only) and `permute`, which only cares how many neighbors are in each
+//! state.
+
+use std::{collections::HashMap, io, str::FromStr};
+
+use crate::{
+    error::Error, grid::Grid, neighborhood::Neighborhood, read::Read, topology::Topology,
+    write::Write,
+};
+
+/// Brian Silverman's Wireworld, with states 0 for empty, 1 for an electron head, 2 for an
+/// electron tail and 3 for a conductor.
+const WIREWORLD: &str = "@RULE Wireworld
+
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+
+var a={0,1,2,3}
+var b={0,1,2,3}
+var c={0,1,2,3}
+var d={0,1,2,3}
+var e={0,1,2,3}
+var f={0,1,2,3}
+var g={0,1,2,3}
+var h={0,1,2,3}
+var i={0,2,3}
+var j={0,2,3}
+var k={0,2,3}
+var l={0,2,3}
+var m={0,2,3}
+var n={0,2,3}
+var o={0,2,3}
+
+# Heads become tails, and tails become conductors.
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# Conductors with one or two neighboring heads become heads.
+3,1,i,j,k,l,m,n,o,1
+3,1,1,i,j,k,l,m,n,1
+
+@COLORS
+0 48 48 48
+1 0 128 255
+2 255 255 255
+3 255 128 0
+";
+
+/// Christopher Langton's self-reproducing loops, from his 1984 paper.
+const LANGTONS_LOOPS: &str = "@RULE Langtons-Loops
+
+@TABLE
+n_states:8
+neighborhood:vonNeumann
+symmetries:rotate4
+
+000000
+000012
+000020
+000030
+000050
+000063
+000071
+000112
+000122
+000132
+000212
+000220
+000230
+000262
+000272
+000320
+000525
+000622
+000722
+001022
+001120
+002020
+002030
+002050
+002125
+002220
+002322
+005222
+012321
+012421
+012525
+012621
+012721
+012751
+014221
+014321
+014421
+014721
+016251
+017221
+017255
+017521
+017621
+017721
+025271
+100011
+100061
+100077
+100111
+100121
+100211
+100244
+100277
+100511
+101011
+101111
+101244
+101277
+102026
+102121
+102211
+102244
+102263
+102277
+102327
+102424
+102626
+102644
+102677
+102710
+102727
+105427
+111121
+111221
+111244
+111251
+111261
+111277
+111522
+112121
+112221
+112244
+112251
+112277
+112321
+112424
+112621
+112727
+113221
+122244
+122277
+122434
+122547
+123244
+123277
+124255
+124267
+125275
+200012
+200022
+200042
+200071
+200122
+200152
+200212
+200222
+200232
+200242
+200250
+200262
+200272
+200326
+200423
+200517
+200522
+200575
+200722
+201022
+201122
+201222
+201422
+201722
+202022
+202032
+202052
+202073
+202122
+202152
+202212
+202222
+202272
+202321
+202422
+202452
+202520
+202552
+202622
+202722
+203122
+203216
+203226
+203422
+204222
+205122
+205212
+205222
+205521
+205725
+206222
+206722
+207122
+207222
+207422
+207722
+211222
+211261
+212222
+212242
+212262
+212272
+214222
+215222
+216222
+217222
+222272
+222442
+222462
+222762
+222772
+300013
+300022
+300041
+300076
+300123
+300421
+300622
+301021
+301220
+302511
+401120
+401220
+401250
+402120
+402221
+402326
+402520
+403221
+500022
+500215
+500225
+500232
+500272
+500520
+502022
+502122
+502152
+502220
+502244
+502722
+512122
+512220
+512422
+512722
+600011
+600021
+602120
+612125
+612131
+612225
+700077
+701120
+701220
+701250
+702120
+702221
+702251
+702321
+702525
+702720
+
+@COLORS
+0 0 0 0
+1 0 0 255
+2 255 0 0
+3 0 255 0
+4 255 255 0
+5 255 0 255
+6 255 255 255
+7 0 255 255
+";
+
+/// Which rearrangements of a transition's neighbors are also transitions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Transitions only match as written.
+    #[default]
+    None,
+    /// Transitions match in any of the four rotations.
+    Rotate4,
+    /// Transitions match in any of the eight rotations, in steps of 45 degrees.
+    Rotate8,
+    /// Transitions match as written or mirrored left to right.
+    Reflect,
+    /// Transitions match in any rotation, mirrored or not.
+    Rotate4Reflect,
+    /// Transitions match in any of the eight rotations, mirrored or not.
+    Rotate8Reflect,
+    /// Transitions match whatever order the neighbors are in.
+    Permute,
+}
+
+impl Symmetry {
+    /// The rearrangements of `neighbors` neighbors, each listing where each neighbor is taken
+    /// from. Permute isn't listed, since it matches differently.
+    fn permutations(self, neighbors: usize) -> Result<Vec<Vec<usize>>, Error> {
+        let rotate = |step: usize| -> Vec<usize> {
+            (0..neighbors).map(|i| (i + step) % neighbors).collect()
+        };
+        let reflect: Vec<usize> = (0..neighbors)
+            .map(|i| (neighbors - i) % neighbors)
+            .collect();
+        // Rotating a quarter turn moves two neighbors round in a Moore neighborhood, but one in a
+        // von Neumann neighborhood.
+        let quarter = neighbors / 4;
+        let generators = match self {
+            Symmetry::None | Symmetry::Permute => vec![],
+            Symmetry::Rotate4 => vec![rotate(quarter)],
+            Symmetry::Rotate8 if neighbors == 8 => vec![rotate(1)],
+            Symmetry::Reflect => vec![reflect],
+            Symmetry::Rotate4Reflect => vec![rotate(quarter), reflect],
+            Symmetry::Rotate8Reflect if neighbors == 8 => vec![rotate(1), reflect],
+            Symmetry::Rotate8 | Symmetry::Rotate8Reflect => {
+                return Err(Error::InvalidRule(
+                    "Eightfold symmetry needs the Moore neighborhood",
+                ))
+            }
+        };
+        // Everything the generators can make between them.
+        let mut permutations = vec![(0..neighbors).collect::<Vec<_>>()];
+        let mut i = 0;
+        while i < permutations.len() {
+            for generator in &generators {
+                let next: Vec<usize> = generator.iter().map(|j| permutations[i][*j]).collect();
+                if !permutations.contains(&next) {
+                    permutations.push(next);
+                }
+            }
+            i += 1;
+        }
+        Ok(permutations)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Symmetry::None),
+            "rotate4" => Ok(Symmetry::Rotate4),
+            "rotate8" => Ok(Symmetry::Rotate8),
+            "reflect" | "reflect_horizontal" => Ok(Symmetry::Reflect),
+            "rotate4reflect" => Ok(Symmetry::Rotate4Reflect),
+            "rotate8reflect" => Ok(Symmetry::Rotate8Reflect),
+            "permute" => Ok(Symmetry::Permute),
+            _ => Err(Error::Unsupported("Rule table symmetry")),
+        }
+    }
+}
+
+/// One of the inputs or the output of a transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    State(u8),
+    Var(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    /// The cell followed by its neighbors.
+    inputs: Vec<Slot>,
+    output: Slot,
+}
+
+/// A multi-state rule given as a table of transitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    states: u16,
+    neighborhood: Neighborhood,
+    symmetry: Symmetry,
+    /// The states each variable stands for.
+    vars: Vec<Vec<u8>>,
+    transitions: Vec<Transition>,
+    /// The rearrangements of the neighbors to try each transition in.
+    permutations: Vec<Vec<usize>>,
+    colors: HashMap<u8, [u8; 3]>,
+}
+
+impl RuleTable {
+    /// Wireworld, with states 0 for empty, 1 for an electron head, 2 for an electron tail and 3
+    /// for a conductor.
+    pub fn wireworld() -> Self {
+        WIREWORLD.parse().expect("Wireworld's table is valid")
+    }
+
+    /// Langton's Loops, the self-reproducing loops from Christopher Langton's 1984 paper.
+    pub fn langtons_loops() -> Self {
+        LANGTONS_LOOPS
+            .parse()
+            .expect("Langton's Loops' table is valid")
+    }
+
+    /// The name of the rule, from its `@RULE` line.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of states.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// The neighborhood the transitions look at, either [Neighborhood::Moore] or
+    /// [Neighborhood::VonNeumann] of radius 1.
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// The symmetries of the transitions.
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    /// The color of a state as red, green and blue, if the rule gives one.
+    pub fn color(&self, state: u8) -> Option<[u8; 3]> {
+        self.colors.get(&state).copied()
+    }
+
+    /// Finds the first transition matching a cell and its neighbors, which are clockwise from
+    /// north, and returns the cell's next state. Returns None if no transition matches.
+    pub fn transition(&self, current: u8, neighbors: &[u8]) -> Option<u8> {
+        let mut bound = vec![None; self.vars.len()];
+        self.transitions.iter().find_map(|transition| {
+            if self.symmetry == Symmetry::Permute {
+                bound.fill(None);
+                let used = vec![false; neighbors.len()];
+                (self.bind(&transition.inputs[0], current, &mut bound)
+                    && self.permuted(&transition.inputs[1..], neighbors, used, &mut bound))
+                .then(|| self.output(transition, &bound))
+            } else {
+                self.permutations.iter().find_map(|permutation| {
+                    bound.fill(None);
+                    let matched = self.bind(&transition.inputs[0], current, &mut bound)
+                        && permutation.iter().enumerate().all(|(i, from)| {
+                            self.bind(&transition.inputs[1 + from], neighbors[i], &mut bound)
+                        });
+                    matched.then(|| self.output(transition, &bound))
+                })
+            }
+        })
+    }
+
+    /// Returns the next state of a cell given its neighbors, clockwise from north. Cells that no
+    /// transition matches keep their state.
+    pub fn next_state(&self, current: u8, neighbors: &[u8]) -> u8 {
+        self.transition(current, neighbors).unwrap_or(current)
+    }
+
+    /// Checks a state against a slot, binding the slot's variable if it isn't yet.
+    fn bind(&self, slot: &Slot, state: u8, bound: &mut [Option<u8>]) -> bool {
+        match slot {
+            Slot::State(s) => *s == state,
+            Slot::Var(var) => match bound[*var] {
+                Some(s) => s == state,
+                None if self.vars[*var].contains(&state) => {
+                    bound[*var] = Some(state);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Matches the slots against the neighbors in any order, backtracking over the choices.
+    fn permuted(
+        &self,
+        slots: &[Slot],
+        neighbors: &[u8],
+        used: Vec<bool>,
+        bound: &mut Vec<Option<u8>>,
+    ) -> bool {
+        let Some((slot, rest)) = slots.split_first() else {
+            return true;
+        };
+        (0..neighbors.len()).any(|i| {
+            if used[i] {
+                return false;
+            }
+            let before = bound.clone();
+            let mut used = used.clone();
+            used[i] = true;
+            if self.bind(slot, neighbors[i], bound) && self.permuted(rest, neighbors, used, bound) {
+                return true;
+            }
+            *bound = before;
+            false
+        })
+    }
+
+    fn output(&self, transition: &Transition, bound: &[Option<u8>]) -> u8 {
+        match transition.output {
+            Slot::State(state) => state,
+            Slot::Var(var) => bound[var].expect("Output variables appear in the inputs"),
+        }
+    }
+}
+
+impl FromStr for RuleTable {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut table = RuleTable {
+            name: String::new(),
+            states: 0,
+            neighborhood: Neighborhood::Moore(1),
+            symmetry: Symmetry::None,
+            vars: Vec::new(),
+            transitions: Vec::new(),
+            permutations: Vec::new(),
+            colors: HashMap::new(),
+        };
+        let mut names: HashMap<String, usize> = HashMap::new();
+        let mut section = "";
+        let mut has_table = false;
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('@') {
+                let (name, rest) = header.split_once(' ').unwrap_or((header, ""));
+                section = name;
+                match name {
+                    "RULE" => table.name = rest.trim().to_string(),
+                    "TABLE" => has_table = true,
+                    _ => {}
+                }
+                continue;
+            }
+            match section {
+                "TABLE" => table.parse_line(line, &mut names)?,
+                "COLORS" => {
+                    let values: Result<Vec<u8>, _> =
+                        line.split_whitespace().map(str::parse).collect();
+                    // Lines with other numbers of values describe gradients, which are skipped.
+                    if let Ok([state, r, g, b]) = values.as_deref() {
+                        table.colors.insert(*state, [*r, *g, *b]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !has_table {
+            return Err(Error::Unsupported("Rule files without a @TABLE"));
+        }
+        if table.states == 0 {
+            return Err(Error::Malformed("Rule table is missing n_states"));
+        }
+        table.permutations = table.symmetry.permutations(table.neighborhood.size())?;
+        Ok(table)
+    }
+}
+
+impl RuleTable {
+    fn parse_line(&mut self, line: &str, names: &mut HashMap<String, usize>) -> Result<(), Error> {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "n_states" => {
+                    self.states = value
+                        .parse()
+                        .ok()
+                        .filter(|states| (2..=256).contains(states))
+                        .ok_or(Error::InvalidRule(
+                            "Rule tables must have between 2 and 256 states",
+                        ))?;
+                }
+                "neighborhood" | "neighbourhood" => {
+                    self.neighborhood = match value {
+                        "Moore" => Neighborhood::Moore(1),
+                        "vonNeumann" => Neighborhood::VonNeumann(1),
+                        _ => return Err(Error::Unsupported("Rule table neighborhood")),
+                    };
+                }
+                "symmetries" => self.symmetry = value.parse()?,
+                _ => return Err(Error::Malformed("Unknown rule table setting")),
+            }
+            return Ok(());
+        }
+        if self.states == 0 {
+            return Err(Error::Malformed("Rule table is missing n_states"));
+        }
+        if let Some(var) = line.strip_prefix("var ") {
+            let (name, set) = var
+                .split_once('=')
+                .ok_or(Error::Malformed("Malformed rule table variable"))?;
+            let set = set
+                .trim()
+                .strip_prefix('{')
+                .and_then(|set| set.strip_suffix('}'))
+                .ok_or(Error::Malformed("Malformed rule table variable"))?;
+            let mut states = Vec::new();
+            for token in set.split(',').map(str::trim) {
+                // Sets can include the states of earlier variables.
+                match self.slot(token, names)? {
+                    Slot::State(state) => states.push(state),
+                    Slot::Var(var) => states.extend(&self.vars[var]),
+                }
+            }
+            names.insert(name.trim().to_string(), self.vars.len());
+            self.vars.push(states);
+            return Ok(());
+        }
+
+        let tokens: Vec<&str> = if line.contains(',') {
+            line.split(',').map(str::trim).collect()
+        } else if !line.is_ascii() {
+            return Err(Error::Malformed("Malformed rule table transition"));
+        } else if self.states <= 10 {
+            (0..line.len()).map(|i| &line[i..i + 1]).collect()
+        } else {
+            return Err(Error::Malformed(
+                "Rule tables with over 10 states need commas",
+            ));
+        };
+        if tokens.len() != self.neighborhood.size() + 2 {
+            return Err(Error::Malformed(
+                "Transition has the wrong number of states",
+            ));
+        }
+        let mut slots = tokens
+            .iter()
+            .map(|token| self.slot(token, names))
+            .collect::<Result<Vec<_>, _>>()?;
+        let output = slots.pop().expect("Transitions have an output");
+        if matches!(output, Slot::Var(_)) && !slots.contains(&output) {
+            return Err(Error::Malformed(
+                "Transition output variable isn't one of its inputs",
+            ));
+        }
+        self.transitions.push(Transition {
+            inputs: slots,
+            output,
+        });
+        Ok(())
+    }
+
+    fn slot(&self, token: &str, names: &HashMap<String, usize>) -> Result<Slot, Error> {
+        if let Some(var) = names.get(token) {
+            return Ok(Slot::Var(*var));
+        }
+        match token.parse::<u8>() {
+            Ok(state) if u16::from(state) < self.states => Ok(Slot::State(state)),
+            Ok(_) => Err(Error::InvalidRule("Rule table state out of range")),
+            Err(_) => Err(Error::Malformed("Unknown rule table variable")),
+        }
+    }
+}
+
+/// Reads a rule table from `.rule` formatted input.
+pub fn read_rule_table(mut input: impl io::Read) -> Result<RuleTable, Error> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+    contents.parse()
+}
+
+/// A fixed size board running a [RuleTable].
+///
+/// The next state of every distinct neighborhood is remembered, so each one is only looked up in
+/// the table once.
+#[derive(Debug, Clone)]
+pub struct TableLife {
+    grid: Grid<u8>,
+    rule: RuleTable,
+    cache: HashMap<[u8; 9], u8>,
+}
+
+impl TableLife {
+    /// Creates a board with every cell in state 0.
+    pub fn new(width: usize, height: usize, rule: RuleTable) -> Self {
+        TableLife {
+            grid: Grid::new(width, height),
+            rule,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Creates a board from a grid of states. Fails if any cell is in a state the rule doesn't
+    /// have.
+    pub fn from_grid(grid: Grid<u8>, rule: RuleTable) -> Result<Self, Error> {
+        if grid.iter().any(|state| u16::from(state) >= rule.states) {
+            return Err(Error::Unsupported("Cells in states the rule doesn't have"));
+        }
+        Ok(TableLife {
+            grid,
+            rule,
+            cache: HashMap::new(),
+        })
+    }
+
+    /// The rule used when transitioning the board.
+    pub fn rule(&self) -> &RuleTable {
+        &self.rule
+    }
+
+    /// Sets the topology of the board, deciding what lies past its edges. Defaults to
+    /// [Topology::Bounded].
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self {
+            grid: self.grid.with_topology(topology),
+            ..self
+        }
+    }
+
+    /// The topology of the board.
+    pub fn topology(&self) -> Topology {
+        self.grid.topology()
+    }
+
+    /// The underlying board of cell states.
+    pub fn grid(&self) -> &Grid<u8> {
+        &self.grid
+    }
+
+    /// Iterate over the cell states of the board, from (0, 0) to `(self.width(), self.height())`
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.grid.iter()
+    }
+
+    /// Advance the board a single generation in place. Cells off the board count as state 0.
+    pub fn step(&mut self) {
+        let (rule, cache) = (&self.rule, &mut self.cache);
+        let von_neumann = rule.neighborhood == Neighborhood::VonNeumann(1);
+        self.grid.step_with(|state, neighbors| {
+            // Neighbors come clockwise from the top left, and tables list them from the top.
+            let neighbors = neighbors.map(Option::unwrap_or_default);
+            let mut key = [state, 0, 0, 0, 0, 0, 0, 0, 0];
+            if von_neumann {
+                for i in 0..4 {
+                    key[1 + i] = neighbors[1 + 2 * i];
+                }
+            } else {
+                for i in 0..8 {
+                    key[1 + i] = neighbors[(i + 1) % 8];
+                }
+            }
+            *cache
+                .entry(key)
+                .or_insert_with(|| rule.next_state(state, &key[1..=rule.neighborhood.size()]))
+        });
+    }
+
+    /// Advance the board `n` generations in place.
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Run the rule against the board and create the next iteration.
+    pub fn transition(&self) -> Self {
+        let mut next = self.clone();
+        next.step();
+        next
+    }
+}
+
+impl PartialEq for TableLife {
+    /// Boards are equal when they have the same cells, topology and rule.
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid && self.rule == other.rule
+    }
+}
+
+impl Eq for TableLife {}
+
+impl Read<u8> for TableLife {
+    fn read_cell(&mut self, x: usize, y: usize) -> Result<u8, Error> {
+        self.grid.read_cell(x, y)
+    }
+
+    fn read_neighbors(&mut self, x: usize, y: usize) -> Result<[Option<u8>; 8], Error> {
+        self.grid.read_neighbors(x, y)
+    }
+
+    fn read_neighborhood(
+        &mut self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> Result<Vec<Option<u8>>, Error> {
+        self.grid.read_neighborhood(x, y, neighborhood)
+    }
+
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+}
+
+impl Write<u8> for TableLife {
+    /// Sets the state of a cell. Fails if the rule doesn't have that state.
+    fn write_cell(&mut self, x: usize, y: usize, state: u8) -> Result<(), Error> {
+        if u16::from(state) >= self.rule.states {
+            return Err(Error::Unsupported("Cells in states the rule doesn't have"));
+        }
+        self.grid.write_cell(x, y, state)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
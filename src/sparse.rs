@@ -0,0 +1,147 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{error::Error, game_of_life::GameOfLife, read::Read, rule::Rule};
+
+/// An unbounded representation of the Game of Life that only stores the live cells, keyed by
+/// signed coordinates. The board has no edges, so patterns like spaceships can travel forever.
+///
+/// Rules where cells are born with zero neighbors (B0) aren't supported, since they would fill the
+/// infinite empty space around the pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseLife {
+    cells: HashSet<(i64, i64)>,
+    rule: Rule,
+}
+
+impl SparseLife {
+    /// Creates an empty board running Conway's Game of Life.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ruleset used when transitioning the board. Fails if the rule has cells born with
+    /// zero neighbors.
+    pub fn with_rule(self, rule: Rule) -> Result<Self, Error> {
+        if rule.is_birth(0) {
+            return Err(Error::Unsupported("Unbounded board rules with B0"));
+        }
+        Ok(Self { rule, ..self })
+    }
+
+    /// The ruleset used when transitioning the board.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Returns the cell at the coordinates provided.
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x, y))
+    }
+
+    /// Sets the cell at the coordinates provided.
+    pub fn set(&mut self, x: i64, y: i64, alive: bool) {
+        if alive {
+            self.cells.insert((x, y));
+        } else {
+            self.cells.remove(&(x, y));
+        }
+    }
+
+    /// Iterate over the coordinates of every live cell, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// The number of live cells on the board.
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The smallest rectangle containing every live cell, as `(min_x, min_y, max_x, max_y)`
+    /// inclusive. Returns None if the board is empty.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.cells.iter().fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => (*x, *y, *x, *y),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+                }
+            })
+        })
+    }
+
+    /// Run the ruleset against the board and create the next iteration. Only live cells and their
+    /// neighbors are considered, so the cost depends on the population rather than the area.
+    pub fn transition(&self) -> Self {
+        let offsets: Vec<_> = self.rule.neighborhood().offsets().collect();
+        let mut counts: HashMap<(i64, i64), usize> = HashMap::with_capacity(self.cells.len() * 4);
+        for (x, y) in &self.cells {
+            // A live cell is a neighbor of the cells it is offset from, not the cells offset from
+            // it, which only differ for lopsided custom neighborhoods.
+            for (dx, dy) in &offsets {
+                *counts.entry((x - *dx as i64, y - *dy as i64)).or_default() += 1;
+            }
+        }
+        let mut cells: HashSet<(i64, i64)> = counts
+            .iter()
+            .filter(|(cell, count)| self.rule.next_state(self.cells.contains(cell), **count))
+            .map(|(cell, _)| *cell)
+            .collect();
+        // Live cells without any live neighbors never made it into the counts.
+        if self.rule.is_survival(0) {
+            cells.extend(self.cells.iter().filter(|cell| !counts.contains_key(cell)));
+        }
+        Self {
+            cells,
+            rule: self.rule,
+        }
+    }
+
+    /// Copies a rectangle of the board, with its top left corner at `(x, y)`, into a
+    /// [GameOfLife].
+    pub fn window(&self, x: i64, y: i64, width: usize, height: usize) -> GameOfLife {
+        let mut cells = vec![vec![false; width]; height];
+        for (c_x, c_y) in &self.cells {
+            let (w_x, w_y) = (c_x - x, c_y - y);
+            if (0..width as i64).contains(&w_x) && (0..height as i64).contains(&w_y) {
+                cells[w_y as usize][w_x as usize] = true;
+            }
+        }
+        GameOfLife::from(cells).with_rule(self.rule)
+    }
+}
+
+impl TryFrom<&GameOfLife> for SparseLife {
+    type Error = Error;
+
+    /// Places the board with its top left corner at the origin.
+    fn try_from(value: &GameOfLife) -> Result<Self, Self::Error> {
+        let width = value.width().max(1);
+        let cells = value
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| *alive)
+            .map(|(i, _)| ((i % width) as i64, (i / width) as i64))
+            .collect();
+        SparseLife {
+            cells,
+            rule: Rule::default(),
+        }
+        .with_rule(value.rule())
+    }
+}
+
+impl From<&SparseLife> for GameOfLife {
+    /// Copies the board into a fixed size board, cropped to the bounding box of the pattern.
+    fn from(value: &SparseLife) -> Self {
+        match value.bounding_box() {
+            Some((min_x, min_y, max_x, max_y)) => value.window(
+                min_x,
+                min_y,
+                (max_x - min_x + 1) as usize,
+                (max_y - min_y + 1) as usize,
+            ),
+            None => GameOfLife::from(Vec::new()).with_rule(value.rule),
+        }
+    }
+}
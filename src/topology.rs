@@ -0,0 +1,89 @@
+//! # Board Topologies
+//! A topology decides what lies beyond the edges of a board. A bounded board is surrounded by dead
+//! cells, while the other topologies glue the edges together so patterns can travel across them.
+
+use crate::{neighborhood::Neighborhood, utils::NEIGHBOR_OFFSETS};
+
+/// The shape of the surface a board is drawn on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// A plane with dead cells past every edge.
+    #[default]
+    Bounded,
+    /// The left and right edges are joined, as are the top and bottom edges.
+    Torus,
+    /// Like a torus, except crossing the top or bottom edge mirrors the x coordinate.
+    KleinBottle,
+    /// The real projective plane. Crossing the left or right edge mirrors the y coordinate, and
+    /// crossing the top or bottom edge mirrors the x coordinate.
+    CrossSurface,
+}
+
+impl Topology {
+    /// Maps a coordinate that may lie off the board back onto a board of the given size. Returns
+    /// None if the coordinate doesn't correspond to any cell.
+    pub fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        if w == 0 || h == 0 {
+            return None;
+        }
+        let (mut x, mut y) = (x, y);
+        match self {
+            Topology::Bounded => {}
+            Topology::Torus => {
+                x = x.rem_euclid(w);
+                y = y.rem_euclid(h);
+            }
+            Topology::KleinBottle => {
+                x = x.rem_euclid(w);
+                if y.div_euclid(h) % 2 != 0 {
+                    x = w - 1 - x;
+                }
+                y = y.rem_euclid(h);
+            }
+            Topology::CrossSurface => {
+                if x.div_euclid(w) % 2 != 0 {
+                    y = h - 1 - y;
+                }
+                x = x.rem_euclid(w);
+                if y.div_euclid(h) % 2 != 0 {
+                    x = w - 1 - x;
+                }
+                y = y.rem_euclid(h);
+            }
+        }
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Returns all 8 neighbor coordinates for the provided cell, in the same order as
+    /// [Read::read_neighbors](crate::read::Read::read_neighbors). Neighbors that don't exist on
+    /// this topology are None.
+    pub fn neighbor_coordinates(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> [Option<(usize, usize)>; 8] {
+        NEIGHBOR_OFFSETS.map(|(dx, dy)| self.wrap(x as isize + dx, y as isize + dy, width, height))
+    }
+
+    /// Returns the coordinates of every cell in the neighborhood of the provided cell, in the
+    /// order of [Neighborhood::offsets]. Neighbors that don't exist on this topology are None.
+    pub fn neighborhood_coordinates(
+        self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = Option<(usize, usize)>> {
+        neighborhood
+            .offsets()
+            .map(move |(dx, dy)| self.wrap(x as isize + dx, y as isize + dy, width, height))
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{error::Error, read::Read};
+
+/// A generic way to modify Game of Life representations.
+///
+/// Implementations may hold writes back until [Write::flush] is called, but reads through
+/// [Read] always see every write made so far.
+pub trait Write<C: Copy = bool>: Read<C> {
+    /// Sets the cell at the coordinates provided.
+    fn write_cell(&mut self, x: usize, y: usize, cell: C) -> Result<(), Error>;
+
+    /// Sets a run of cells in row `y`, starting at column `x`. Nothing is written if any of the
+    /// cells would fall off the board.
+    fn write_row(&mut self, x: usize, y: usize, cells: &[C]) -> Result<(), Error> {
+        let end = x.checked_add(cells.len());
+        if end.is_none_or(|end| end > self.width()) || y >= self.height() {
+            return Err(Error::OutOfBounds {
+                x: x.saturating_add(cells.len().saturating_sub(1)),
+                y,
+            });
+        }
+        for (i, cell) in cells.iter().enumerate() {
+            self.write_cell(x + i, y, *cell)?;
+        }
+        Ok(())
+    }
+
+    /// Sets every cell in the rectangle with its top left corner at `(x, y)`. Nothing is written
+    /// if any of the rectangle would fall off the board.
+    fn fill_rect(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        cell: C,
+    ) -> Result<(), Error> {
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let (last_x, last_y) = (x.checked_add(width - 1), y.checked_add(height - 1));
+        if last_x.is_none_or(|last_x| last_x >= self.width())
+            || last_y.is_none_or(|last_y| last_y >= self.height())
+        {
+            return Err(Error::OutOfBounds {
+                x: x.saturating_add(width - 1),
+                y: y.saturating_add(height - 1),
+            });
+        }
+        let row = vec![cell; width];
+        for row_y in y..y + height {
+            self.write_row(x, row_y, &row)?;
+        }
+        Ok(())
+    }
+
+    /// Makes sure every write so far has reached the underlying storage.
+    fn flush(&mut self) -> Result<(), Error>;
+}